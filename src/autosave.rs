@@ -0,0 +1,224 @@
+//! Saving the board every so often, and getting it back after a crash.
+//!
+//! While the game is open there's a session.lock file in BASE_DIR, which is deleted when it closes
+//! normally. If it's still there when the game starts, the last session crashed and the latest
+//! autosave is offered back. The lock file is also locked while the game is open, so a second
+//! instance can tell the first one is still running rather than crashed.
+
+use crate::prelude::*;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Autosave names start with a timestamp_id, which is always this long.
+const TIMESTAMP_LEN: usize = "YYYY-MM-DD_HH-MM-SS".len();
+
+/// Saves the board if CONFIG.autosave_interval has passed since the last autosave.
+pub fn autosave_if_due(model: &mut Model) {
+    if CONFIG.autosave_interval == 0
+        || model.last_autosave.elapsed() < Duration::from_secs(CONFIG.autosave_interval)
+    {
+        return;
+    }
+
+    model.last_autosave = Instant::now();
+    autosave(model);
+}
+
+/// Saves the board to the autosaves folder in the background, deleting the oldest autosaves past
+/// CONFIG.autosave_count.
+pub fn autosave(model: &mut Model) {
+    let mut savestate = SaveState::from_model(model, String::new());
+    savestate.metadata.description = "Autosave".to_string();
+
+    let job = Job::spawn("autosaving", move |_| {
+        match write_autosave(&autosave_dir(), &mut savestate, CONFIG.autosave_count) {
+            Ok(_) => JobOutput::Done,
+            Err(e) => JobOutput::Error(format!("Autosave failed: {}", e)),
+        }
+    });
+    // Not start_job, there's no need to say so every time.
+    model.jobs.push(job);
+}
+
+/// Writes an autosave to `dir`, named after the time, and deletes the oldest ones so only `keep`
+/// are left (at least 1, so the new one is never deleted). Returns the new autosave's name.
+pub fn write_autosave(
+    dir: &str,
+    savestate: &mut SaveState,
+    keep: usize,
+) -> Result<String, SaveError> {
+    // Numbered after the last autosave from the same second, even if older ones were deleted, so
+    // the names stay in order.
+    let time = timestamp_id();
+    let last = list_autosaves(dir)
+        .iter()
+        .map(|name| autosave_order(name))
+        .filter(|(other, _)| *other == time)
+        .map(|(_, count)| count)
+        .max();
+    let name = match last {
+        Some(count) => format!("{}-{}", time, count + 1),
+        None => time,
+    };
+    savestate.metadata.name = name.clone();
+
+    fs::create_dir_all(dir)?;
+    fs::write(autosave_path(dir, &name), savestate.to_bytes())?;
+
+    let autosaves = list_autosaves(dir);
+    for old in &autosaves[..autosaves.len().saturating_sub(keep.max(1))] {
+        fs::remove_file(autosave_path(dir, old))?;
+    }
+
+    Ok(name)
+}
+
+/// The names of the autosaves in `dir`, oldest first.
+pub fn list_autosaves(dir: &str) -> Vec<String> {
+    let Ok(dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = dir
+        .flatten()
+        .map(|file| file.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "gol"))
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+        .collect();
+    // The names are timestamps, with a -2, -3, etc. on the end when there's more than one in the
+    // same second, so this puts them in the order they were made.
+    names.sort_by_cached_key(|name| autosave_order(name));
+
+    names
+}
+
+/// The time an autosave was made, and which one it was in that second (starting at 1).
+fn autosave_order(name: &str) -> (String, usize) {
+    let split = match name.is_char_boundary(TIMESTAMP_LEN) {
+        true => TIMESTAMP_LEN,
+        false => name.len(),
+    };
+    let (time, count) = name.split_at(split);
+    let count = count.trim_start_matches('-').parse().unwrap_or(1);
+
+    (time.to_string(), count)
+}
+
+pub fn load_autosave(name: &str) -> Result<SaveState, SaveError> {
+    let bytes = fs::read(autosave_path(&autosave_dir(), name))?;
+
+    SaveState::from_bytes(&bytes)
+}
+
+/// The session lock, held while the game is open.
+#[derive(Debug)]
+pub struct Session {
+    /// Kept open, since closing it would release the lock.
+    _lock: File,
+    path: String,
+    /// Whether the last session crashed.
+    pub crashed: bool,
+}
+
+impl Session {
+    /// Marks the session as closed normally.
+    pub fn end(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Marks the session as running, using the lock file at `path`. Returns None if another instance
+/// of the game has it, in which case that instance is the one keeping track of crashes.
+pub fn start_session(path: &str) -> Option<Session> {
+    let crashed = Path::new(path).exists();
+
+    if let Some(dir) = Path::new(path).parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let lock = match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(lock) => lock,
+        Err(e) => {
+            eprintln!("Failed to write the session lock: {}", e);
+            return None;
+        }
+    };
+
+    match lock.try_lock() {
+        Ok(()) => {
+            let _ = lock.set_len(0);
+            let _ = (&lock).write_all(std::process::id().to_string().as_bytes());
+            Some(Session {
+                _lock: lock,
+                path: path.to_string(),
+                crashed,
+            })
+        }
+        Err(TryLockError::WouldBlock) => None,
+        Err(TryLockError::Error(e)) => {
+            eprintln!("Failed to lock the session lock: {}", e);
+            None
+        }
+    }
+}
+
+/// Marks the session as closed normally. Used as nannou's exit function.
+pub fn end_session(_app: &App, model: Model) {
+    if let Some(session) = &model.session {
+        session.end();
+    }
+}
+
+/// Opens the prompt asking whether to restore the latest autosave. Escape dismisses it instead of
+/// closing the game.
+pub fn offer_recovery(app: &App, model: &mut Model) {
+    if let Some(name) = list_autosaves(&autosave_dir()).pop() {
+        app.set_exit_on_escape(false);
+        model.recovery = Some(name);
+    }
+}
+
+/// Handles a key press while the recovery prompt is open. Y restores, anything else dismisses.
+pub fn recovery_key_pressed(app: &App, model: &mut Model, key: Key) {
+    let Some(name) = model.recovery.take() else {
+        return;
+    };
+    app.set_exit_on_escape(true);
+
+    if key == Key::Y {
+        match load_autosave(&name) {
+            Ok(savestate) => {
+                apply_savestate(model, &savestate);
+                model.paused = true;
+                notify_info("Autosave restored.");
+            }
+            Err(e) => notify_error(e),
+        }
+    }
+}
+
+pub fn draw_recovery(draw: &Draw, cache: &Cache, model: &Model) {
+    if let Some(name) = &model.recovery {
+        UiWindow::new()
+            .text(format!(
+                "The game didn't close properly last time.\nRestore the autosave from {}?\n\nY: Restore. Any other key: Ignore.",
+                name
+            ))
+            .open(true)
+            .build()
+            .render(draw, cache, model);
+    }
+}
+
+fn autosave_dir() -> String {
+    BASE_DIR.to_string() + "/autosaves"
+}
+
+fn autosave_path(dir: &str, name: &str) -> String {
+    format!("{}/{}.gol", dir, name)
+}
+
+pub fn session_lock_path() -> String {
+    BASE_DIR.to_string() + "/session.lock"
+}
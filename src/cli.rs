@@ -0,0 +1,141 @@
+//! Command line arguments, and the subcommands that run without opening a window.
+
+use crate::formats::{
+    import_image, is_image, read_pattern, write_pattern, ImageImport, MacroCell, PatternFile,
+};
+use crate::formats::{read_pattern_or_savestate, record, write_png, write_svg};
+use crate::formats::{RasterOptions, RecordOptions, SvgOptions};
+use crate::savestates::load_savestate;
+use crate::search::methuselah::{self, MethuselahOptions};
+use crate::search::periodic::{self, PeriodicOptions};
+use clap::{Parser, Subcommand};
+use std::fs;
+
+/// Struct which holds the arguments that can be passed to the program.
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// Used to load a savestate with a given name when launching the program.
+    #[arg(short, long)]
+    pub load: Option<String>,
+
+    /// Print out a savestate with a given name instead of launching the program, used for
+    /// debugging.
+    #[arg(short, long)]
+    pub print: Option<String>,
+
+    /// Run something without opening a window.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Things that can be run headless (without a window).
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Search small seeds for methuselahs, keeping a leaderboard of the longest lived ones.
+    Methuselah(MethuselahOptions),
+    /// Search for oscillators and spaceships with a given period and displacement.
+    Search(PeriodicOptions),
+    /// Convert a pattern file to another format, picked by the output's extension (.rle, .cells,
+    /// .lif or .mc). The input can also be an image, which is converted using the image settings
+    /// in the config.
+    Convert { input: String, output: String },
+    /// Draw a pattern file or a savestate (by name) to a PNG image, or to an SVG if the output
+    /// ends in .svg.
+    Render {
+        input: String,
+        output: String,
+        /// Pixels (or SVG units) per cell.
+        #[arg(short, long, default_value_t = 4)]
+        scale: u32,
+        /// Cells per pixel, for shrinking big patterns (PNG only).
+        #[arg(long, default_value_t = 1)]
+        cells_per_pixel: u32,
+        /// Draw grid lines between the cells (needs a scale of at least 3 in PNGs).
+        #[arg(short, long)]
+        grid: bool,
+        /// Number the rows and columns (SVG only).
+        #[arg(long)]
+        labels: bool,
+    },
+    /// Record a run of a pattern or savestate as an animated GIF, or as a folder of PNG frames.
+    Record(RecordOptions),
+}
+
+/// Runs a headless command.
+pub fn run_headless(command: Command) {
+    match command {
+        Command::Methuselah(options) => methuselah::run(options),
+        Command::Search(options) => periodic::run(options),
+        Command::Record(options) => record(options),
+        Command::Convert { input, output } => {
+            // Macrocell to macrocell doesn't need expanding, so it works for any size.
+            let result = if [&input, &output].iter().all(|path| path.ends_with(".mc")) {
+                fs::read_to_string(&input)
+                    .map_err(|e| format!("Failed to read {}: {}", input, e))
+                    .and_then(|text| MacroCell::parse(&text))
+                    .and_then(|macrocell| {
+                        fs::write(&output, macrocell.to_text())
+                            .map_err(|e| format!("Failed to write {}: {}", output, e))
+                    })
+            } else if is_image(&input) {
+                import_image(&input, &ImageImport::from_config(None))
+                    .and_then(|tiles| write_pattern(&output, &PatternFile::new(tiles)))
+            } else {
+                read_pattern(&input)
+                    .inspect(print_warnings)
+                    .and_then(|file| write_pattern(&output, &file))
+            };
+            match result {
+                Ok(()) => println!("Converted {} to {}.", input, output),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Render {
+            input,
+            output,
+            scale,
+            cells_per_pixel,
+            grid,
+            labels,
+        } => {
+            let result = read_pattern_or_savestate(&input)
+                .inspect(print_warnings)
+                .and_then(|file| match output.ends_with(".svg") {
+                    true => write_svg(&output, &file.tiles, &SvgOptions::new(scale, grid, labels)),
+                    false => {
+                        let options =
+                            RasterOptions::new(scale, grid).cells_per_pixel(cells_per_pixel);
+                        write_png(&output, &file.tiles, &options)
+                    }
+                });
+            match result {
+                Ok(()) => println!("Rendered {} to {}.", input, output),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+fn print_warnings(file: &PatternFile) {
+    for warning in &file.warnings {
+        eprintln!("{}", warning);
+    }
+}
+
+/// Prints out a savestate, for --print.
+pub fn print_savestate(name: String) {
+    match load_savestate(name) {
+        Ok(savestate) => savestate.board.print(),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
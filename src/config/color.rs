@@ -29,6 +29,15 @@ impl Color {
     pub fn to_srgb(self) -> Srgb {
         Srgb::new(self.r, self.g, self.b)
     }
+    /// The color as numbers between 0 and 255, for writing images.
+    pub fn to_rgb8(self) -> [u8; 3] {
+        [self.r, self.g, self.b].map(|c| (c.clamp(0., 1.) * 255.).round() as u8)
+    }
+    /// The color as a hex string like #ff00ff, for SVGs.
+    pub fn to_hex(self) -> String {
+        let [r, g, b] = self.to_rgb8();
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
     /// Create new Color from numbers between 0 and 1.
     pub fn new(r: f32, g: f32, b: f32) -> Self {
         Self { r, g, b }
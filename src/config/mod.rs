@@ -14,7 +14,11 @@ lazy_static! {
 }
 
 /// Struct that holds all the configuration.
+///
+/// Anything missing from config.toml gets its default value, so adding a new option doesn't reset
+/// everyone's config.
 #[derive(Deserialize, Serialize)]
+#[serde(default)]
 pub struct Config {
     pub window_title: String,
     pub tile_size: f32,
@@ -39,6 +43,60 @@ pub struct Config {
     pub window_color: Color,
     pub info_color: Color,
     pub error_color: Color,
+    /// Period of the oscillators/spaceships searched for from inside the game.
+    pub search_period: usize,
+    /// How far spaceships move each period, (0, 0) searches for oscillators.
+    pub search_displacement: [i32; 2],
+    /// Chance of each cell being alive when randomizing, from 0 to 1.
+    pub soup_density: f64,
+    pub soup_symmetry: SoupSymmetry,
+    /// Always randomize with this seed, instead of picking a new one each time.
+    pub soup_seed: Option<u64>,
+    /// Format used by Ctrl+E: "rle", "plaintext", "life105", "life106" or "macrocell".
+    pub export_format: Format,
+    /// Save the board size and where the pattern is, so loading a savestate puts everything back
+    /// exactly where it was. Otherwise savestates are cropped, and centered when they're loaded.
+    pub keep_savestate_layout: bool,
+    /// How images are turned into cells: "threshold", "floydsteinberg" or "ordered".
+    pub image_dither: Dither,
+    /// Pixels darker than this (0-255) become live cells when importing an image.
+    pub image_threshold: u8,
+    /// Make light pixels alive instead of dark ones when importing an image.
+    pub image_invert: bool,
+    /// Shrink imported images to fit on the board.
+    pub image_fit_board: bool,
+    /// Pixels per cell when exporting images with Ctrl+P.
+    pub image_export_scale: u32,
+    /// Cells per pixel when exporting images with Ctrl+P, for shrinking big boards.
+    pub image_export_cells_per_pixel: u32,
+    /// Which cells count as touching for the magic wand: moore (with diagonals) or vonneumann.
+    pub wand_adjacency: Adjacency,
+    /// How many dead cells there can be between live cells for the magic wand to count them as
+    /// touching.
+    pub wand_gap: usize,
+    /// How many generations Ctrl+N advances the selection by.
+    pub evolve_generations: usize,
+    /// How many clips the clipboard history keeps.
+    pub clipboard_history: usize,
+    /// How pastes are combined with the board at first: copy, or, and or xor. Tab changes it while
+    /// pasting.
+    pub paste_mode: PasteMode,
+    /// Extra folders of patterns to show in the library, as well as BASE_DIR/library.
+    pub library_dirs: Vec<String>,
+    /// Units per cell in SVGs exported with V.
+    pub svg_scale: u32,
+    /// Number the rows and columns in exported SVGs.
+    pub svg_labels: bool,
+    /// Only record every Nth generation with Ctrl+G.
+    pub record_stride: usize,
+    /// Milliseconds between frames in recorded GIFs.
+    pub record_delay: u32,
+    /// Save recordings as a folder of PNG frames instead of a GIF.
+    pub record_frames: bool,
+    /// Seconds between autosaves, 0 turns autosaving off.
+    pub autosave_interval: u64,
+    /// How many autosaves to keep (at least 1), older ones are deleted.
+    pub autosave_count: usize,
 }
 
 impl Config {
@@ -49,7 +107,7 @@ impl Config {
         let text = fs::read_to_string(&config_path);
 
         if let Ok(text) = text {
-            return toml::from_str(text.as_str()).unwrap_or(Config::default());
+            toml::from_str(text.as_str()).unwrap_or(Config::default())
         } else {
             notify_info("Failed to read config file; using default values.");
 
@@ -110,6 +168,32 @@ impl Default for Config {
             window_color: Color::new(0.2, 0.2, 0.2),
             info_color: Color::hex(0x51aee9),
             error_color: Color::hex(0xcc6b70),
+            search_period: 3,
+            search_displacement: [0, 0],
+            soup_density: 0.5,
+            soup_symmetry: SoupSymmetry::C1,
+            soup_seed: None,
+            export_format: Format::Rle,
+            keep_savestate_layout: false,
+            image_dither: Dither::Threshold,
+            image_threshold: 128,
+            image_invert: false,
+            image_fit_board: true,
+            image_export_scale: 4,
+            image_export_cells_per_pixel: 1,
+            wand_adjacency: Adjacency::Moore,
+            wand_gap: 0,
+            evolve_generations: 10,
+            clipboard_history: 9,
+            paste_mode: PasteMode::Copy,
+            library_dirs: Vec::new(),
+            svg_scale: 10,
+            svg_labels: true,
+            record_stride: 1,
+            record_delay: 100,
+            record_frames: false,
+            autosave_interval: 300,
+            autosave_count: 5,
         }
     }
 }
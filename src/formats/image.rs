@@ -0,0 +1,138 @@
+//! Turning images (PNG, BMP, etc.) into cells.
+//!
+//! The image is made grayscale, with transparent parts treated as white, and dark pixels become
+//! live cells (or light ones, with `invert`).
+
+use crate::prelude::*;
+use nannou::image::imageops::{self, FilterType};
+use nannou::image::{self as img, GrayImage};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// How gray pixels are turned into live or dead cells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Dither {
+    /// Everything darker than the threshold is alive.
+    #[default]
+    Threshold,
+    /// Spreads each pixel's error onto its neighbors, which keeps gradients looking right.
+    FloydSteinberg,
+    /// Compares each pixel against a repeating 4x4 Bayer matrix, giving a regular pattern.
+    Ordered,
+}
+
+impl fmt::Display for Dither {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Dither::Threshold => "threshold",
+            Dither::FloydSteinberg => "Floyd-Steinberg",
+            Dither::Ordered => "ordered",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Settings for importing an image.
+#[derive(Clone, Copy, Debug)]
+pub struct ImageImport {
+    pub dither: Dither,
+    /// Pixels darker than this (0-255) are alive.
+    pub threshold: u8,
+    /// Make light pixels alive instead of dark ones.
+    pub invert: bool,
+    /// Shrink the image to fit in this many cells, keeping its aspect ratio.
+    pub fit: Option<(usize, usize)>,
+}
+
+impl ImageImport {
+    /// The settings from the config, fitting the image to a board if one's given.
+    pub fn from_config(fit: Option<(usize, usize)>) -> Self {
+        Self {
+            dither: CONFIG.image_dither,
+            threshold: CONFIG.image_threshold,
+            invert: CONFIG.image_invert,
+            fit,
+        }
+    }
+}
+
+/// Whether a path looks like an image, going by its extension.
+pub fn is_image(path: impl AsRef<Path>) -> bool {
+    img::ImageFormat::from_path(path).is_ok()
+}
+
+/// Reads an image file into cells, bottom row first like the board.
+pub fn import_image(path: impl AsRef<Path>, options: &ImageImport) -> Result<Grid<bool>, String> {
+    let path = path.as_ref();
+    let image = img::open(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        .to_luma_alpha8();
+
+    // Transparent pixels count as white.
+    let mut gray = GrayImage::from_fn(image.width(), image.height(), |x, y| {
+        let [luma, alpha] = image.get_pixel(x, y).0;
+        let alpha = alpha as u32;
+        img::Luma([((luma as u32 * alpha + 255 * (255 - alpha)) / 255) as u8])
+    });
+
+    if let Some((max_width, max_height)) = options.fit {
+        let (width, height) = (gray.width() as f64, gray.height() as f64);
+        let scale = (max_width as f64 / width).min(max_height as f64 / height);
+        if scale < 1. {
+            let (w, h) = ((width * scale).max(1.), (height * scale).max(1.));
+            gray = imageops::resize(&gray, w as u32, h as u32, FilterType::Triangle);
+        }
+    }
+
+    Ok(image_to_cells(&gray, options))
+}
+
+/// Turns a grayscale image into cells.
+pub fn image_to_cells(gray: &GrayImage, options: &ImageImport) -> Grid<bool> {
+    const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+    let (width, height) = (gray.width() as usize, gray.height() as usize);
+    let mut values: Vec<f32> = gray
+        .pixels()
+        .map(|pixel| match options.invert {
+            true => 255. - pixel.0[0] as f32,
+            false => pixel.0[0] as f32,
+        })
+        .collect();
+    let threshold = options.threshold as f32;
+
+    let mut tiles = Grid::new(height, width);
+    for y in 0..height {
+        for x in 0..width {
+            let value = values[y * width + x];
+            let alive = match options.dither {
+                Dither::Threshold => value < threshold,
+                Dither::Ordered => value < (BAYER[y % 4][x % 4] as f32 + 0.5) / 16. * 255.,
+                Dither::FloydSteinberg => {
+                    let alive = value < threshold;
+                    let error = value - if alive { 0. } else { 255. };
+
+                    let mut spread = |dx: isize, dy: usize, weight: f32| {
+                        let nx = x as isize + dx;
+                        if nx >= 0 && (nx as usize) < width && y + dy < height {
+                            values[(y + dy) * width + nx as usize] += error * weight;
+                        }
+                    };
+                    spread(1, 0, 7. / 16.);
+                    spread(-1, 1, 3. / 16.);
+                    spread(0, 1, 5. / 16.);
+                    spread(1, 1, 1. / 16.);
+
+                    alive
+                }
+            };
+
+            // Images go top to bottom, the board goes bottom to top.
+            tiles[(height - 1 - y, x)] = alive;
+        }
+    }
+
+    tiles
+}
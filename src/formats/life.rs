@@ -0,0 +1,180 @@
+//! The Life 1.05 and Life 1.06 formats, from the old DOS program Life.
+//!
+//! Life 1.06 is a list of live cell coordinates:
+//!
+//! ```text
+//! #Life 1.06
+//! 1 -1
+//! 2 0
+//! 0 1
+//! ```
+//!
+//! Life 1.05 is blocks of cells (`*` alive, `.` dead), each starting with `#P x y` for the
+//! position of its top left corner. `#D` lines are comments, and `#N` (normal rules) or `#R` (an
+//! S/B rulestring) give the rule.
+//!
+//! In both, y goes down.
+
+use super::{read_rule, PatternFile};
+use crate::prelude::*;
+
+/// Turns a list of cells (with y going down) into a grid that just fits them, as long as it's no
+/// bigger than MAX_PATTERN_SIZE.
+fn cells_to_grid(cells: &[(i64, i64)]) -> Result<Grid<bool>, String> {
+    let Some(min_x) = cells.iter().map(|(x, _)| *x).min() else {
+        return Ok(Grid::new(0, 0));
+    };
+    let min_y = cells.iter().map(|(_, y)| *y).min().unwrap_or(0);
+    let max_x = cells.iter().map(|(x, _)| *x).max().unwrap_or(0);
+    let max_y = cells.iter().map(|(_, y)| *y).max().unwrap_or(0);
+
+    let size = |min: i64, max: i64| {
+        let size = usize::try_from(max.checked_sub(min)?)
+            .ok()?
+            .checked_add(1)?;
+        (size <= MAX_PATTERN_SIZE).then_some(size)
+    };
+    let (Some(width), Some(height)) = (size(min_x, max_x), size(min_y, max_y)) else {
+        return Err(format!(
+            "The pattern is bigger than {0}x{0}.",
+            MAX_PATTERN_SIZE
+        ));
+    };
+
+    let mut tiles = Grid::new(height, width);
+    for (x, y) in cells {
+        tiles[(height - 1 - (y - min_y) as usize, (x - min_x) as usize)] = true;
+    }
+
+    Ok(tiles)
+}
+
+/// Every live cell in a grid, with y going down.
+fn grid_to_cells(tiles: &Grid<bool>) -> impl Iterator<Item = (usize, usize)> + '_ {
+    (0..tiles.rows())
+        .rev()
+        .enumerate()
+        .flat_map(move |(y, row)| (0..tiles.cols()).map(move |x| (x, y, row)))
+        .filter(|(x, _, row)| tiles[(*row, *x)])
+        .map(|(x, y, _)| (x, y))
+}
+
+/// Reads a Life 1.06 file.
+pub fn parse_life106(text: &str) -> Result<PatternFile, String> {
+    let mut file = PatternFile::default();
+    let mut cells = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if let Some(comment) = line.strip_prefix('#') {
+            if !comment.starts_with("Life") {
+                file.comments.push(comment.trim().to_string());
+            }
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace().map(|n| n.parse::<i64>());
+        match (parts.next(), parts.next()) {
+            (Some(Ok(x)), Some(Ok(y))) => cells.push((x, y)),
+            _ => return Err(format!("Invalid coordinate '{}'.", line)),
+        }
+    }
+
+    file.tiles = cells_to_grid(&cells)?;
+
+    Ok(file)
+}
+
+/// Writes a pattern as Life 1.06, with the top left corner at (0, 0).
+pub fn to_life106(file: &PatternFile) -> String {
+    let mut text = String::from("#Life 1.06\n");
+
+    for (x, y) in grid_to_cells(&file.tiles) {
+        text += &format!("{} {}\n", x, y);
+    }
+
+    text
+}
+
+/// Reads a Life 1.05 file.
+pub fn parse_life105(text: &str) -> Result<PatternFile, String> {
+    let mut file = PatternFile::default();
+    let mut cells = Vec::new();
+    let (mut block_x, mut block_y) = (0, 0);
+    let mut y = 0;
+
+    for line in text.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("#P") {
+            let mut parts = rest.split_whitespace().map(|n| n.parse::<i64>());
+            match (parts.next(), parts.next()) {
+                (Some(Ok(x)), Some(Ok(new_y))) => (block_x, block_y) = (x, new_y),
+                _ => return Err(format!("Invalid position '{}'.", line)),
+            }
+            y = 0;
+        } else if let Some(rest) = line.strip_prefix("#R") {
+            read_rule(&mut file.rule, &mut file.warnings, rest);
+        } else if line.starts_with("#N") {
+            file.rule = Some(Rule::parse("23/3")?);
+        } else if let Some(rest) = line.strip_prefix("#D") {
+            file.comments.push(rest.trim().to_string());
+        } else if line.starts_with('#') || line.is_empty() {
+            continue;
+        } else {
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '*' | 'O' | 'o' => {
+                        let cell = block_x.checked_add(x as i64).zip(block_y.checked_add(y));
+                        cells.push(cell.ok_or("A cell is too far away.")?);
+                    }
+                    '.' => (),
+                    c => return Err(format!("Unexpected '{}' in pattern.", c)),
+                }
+            }
+            y += 1;
+        }
+    }
+
+    file.tiles = cells_to_grid(&cells)?;
+
+    Ok(file)
+}
+
+/// Writes a pattern as Life 1.05, as one block.
+pub fn to_life105(file: &PatternFile) -> String {
+    let tiles = &file.tiles;
+    let mut text = String::from("#Life 1.05\n");
+
+    if let Some(name) = &file.name {
+        text += &format!("#D {}\n", name);
+    }
+    for comment in &file.comments {
+        text += &format!("#D {}\n", comment);
+    }
+    match file.rule {
+        Some(rule) if rule != Rule::parse("23/3").unwrap() => {
+            text += &format!("#R {}\n", rule.serialize())
+        }
+        _ => text += "#N\n",
+    }
+
+    text += &format!(
+        "#P {} {}\n",
+        -(tiles.cols() as i64 / 2),
+        -(tiles.rows() as i64 / 2)
+    );
+    for row in (0..tiles.rows()).rev() {
+        let line: String = (0..tiles.cols())
+            .map(|x| if tiles[(row, x)] { '*' } else { '.' })
+            .collect();
+        // Empty lines are skipped when reading, so an empty row is a single dot.
+        match line.trim_end_matches('.') {
+            "" => text.push('.'),
+            line => text += line,
+        }
+        text.push('\n');
+    }
+
+    text
+}
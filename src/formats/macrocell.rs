@@ -0,0 +1,362 @@
+//! Golly's macrocell format (.mc), a quadtree where identical squares are only stored once.
+//!
+//! ```text
+//! [M2] (golly 2.0)
+//! #R B3/S23
+//! .*$..*$***$
+//! 4 1 0 0 0
+//! ```
+//!
+//! Every line after the comments is a node, numbered from 1. A line of `.`, `*` and `$` is an 8x8
+//! leaf (rows end with `$`, y goes down). A line of five numbers is a node of that level (a
+//! 2^level square) followed by its four children: top left, top right, bottom left, bottom right.
+//! A child of 0 is empty. The last node is the whole pattern.
+//!
+//! Huge patterns can be loaded and saved as a quadtree without ever expanding them. They only have
+//! to be expanded to go on the board, which has a size limit.
+
+use super::{read_rule, PatternFile, MAX_PATTERN_SIZE};
+use crate::prelude::*;
+use std::collections::HashMap;
+
+/// The biggest pattern (in either direction) that will be expanded to go on the board.
+pub const MAX_EXPANDED_SIZE: i64 = MAX_PATTERN_SIZE as i64;
+
+/// The deepest node allowed. Cell positions inside a level 62 node still fit in an i64.
+const MAX_LEVEL: usize = 62;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Node {
+    /// An 8x8 square, one bit per cell, starting at the top left.
+    Leaf(u64),
+    /// A 2^level square, made of four 2^(level - 1) squares (0 means empty).
+    Inner { level: u32, children: [usize; 4] },
+}
+
+/// A pattern stored as a macrocell quadtree.
+#[derive(Clone, Debug, Default)]
+pub struct MacroCell {
+    /// Node n is at index n - 1, the last one is the root.
+    nodes: Vec<Node>,
+    pub rule: Option<Rule>,
+    pub comments: Vec<String>,
+    /// Things in the file that were skipped over, to show to the user.
+    pub warnings: Vec<String>,
+}
+
+impl MacroCell {
+    /// Reads a macrocell file.
+    pub fn parse(text: &str) -> Result<MacroCell, String> {
+        let mut macrocell = MacroCell::default();
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with("[M2]") {
+                continue;
+            }
+
+            if let Some(comment) = line.strip_prefix('#') {
+                let (kind, rest) = comment.split_at(comment.len().min(1));
+                match kind {
+                    "R" => read_rule(&mut macrocell.rule, &mut macrocell.warnings, rest),
+                    "C" => macrocell.comments.push(rest.trim().to_string()),
+                    _ => (),
+                }
+                continue;
+            }
+
+            let node = if line.starts_with(|c: char| c.is_ascii_digit()) {
+                let numbers = line
+                    .split_whitespace()
+                    .map(|n| n.parse::<usize>())
+                    .collect::<Result<Vec<usize>, _>>()
+                    .map_err(|_| format!("Invalid node '{}'.", line))?;
+
+                let &[level, nw, ne, sw, se] = numbers.as_slice() else {
+                    return Err(format!("Invalid node '{}'.", line));
+                };
+                if level < 4 {
+                    return Err("Only two state macrocell files are supported.".to_string());
+                }
+                if level > MAX_LEVEL {
+                    return Err(format!("Node '{}' is too deep.", line));
+                }
+                let children = [nw, ne, sw, se];
+                for child in children {
+                    if child > macrocell.nodes.len()
+                        || (child > 0 && macrocell.level(child) != level as u32 - 1)
+                    {
+                        return Err(format!("Invalid child in node '{}'.", line));
+                    }
+                }
+
+                Node::Inner {
+                    level: level as u32,
+                    children,
+                }
+            } else {
+                let mut bits = 0;
+                for (y, row) in line.split('$').enumerate() {
+                    for (x, c) in row.chars().enumerate() {
+                        match c {
+                            '*' if x < 8 && y < 8 => bits |= 1 << (y * 8 + x),
+                            '.' => (),
+                            _ => return Err(format!("Invalid leaf '{}'.", line)),
+                        }
+                    }
+                }
+                Node::Leaf(bits)
+            };
+
+            macrocell.nodes.push(node);
+        }
+
+        if macrocell.nodes.is_empty() {
+            return Err("The file has no nodes.".to_string());
+        }
+
+        Ok(macrocell)
+    }
+    /// Writes the quadtree as a macrocell file.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("[M2] (gol {})\n", VERSION);
+
+        if let Some(rule) = self.rule {
+            text += &format!("#R {}\n", rule.to_bs());
+        }
+        for comment in &self.comments {
+            text += &format!("#C {}\n", comment);
+        }
+
+        for node in &self.nodes {
+            match node {
+                Node::Leaf(bits) => {
+                    let rows: Vec<String> = (0..8)
+                        .map(|y| {
+                            let row: String = (0..8)
+                                .map(|x| {
+                                    if bits >> (y * 8 + x) & 1 == 1 {
+                                        '*'
+                                    } else {
+                                        '.'
+                                    }
+                                })
+                                .collect();
+                            row.trim_end_matches('.').to_string()
+                        })
+                        .collect();
+                    let last = rows
+                        .iter()
+                        .rposition(|row| !row.is_empty())
+                        .map_or(0, |i| i + 1);
+                    for row in &rows[..last.max(1)] {
+                        text += row;
+                        text.push('$');
+                    }
+                }
+                Node::Inner { level, children } => {
+                    let [nw, ne, sw, se] = children;
+                    text += &format!("{} {} {} {} {}", level, nw, ne, sw, se);
+                }
+            }
+            text.push('\n');
+        }
+
+        text
+    }
+    fn node(&self, index: usize) -> Node {
+        self.nodes[index - 1]
+    }
+    fn level(&self, index: usize) -> u32 {
+        match self.node(index) {
+            Node::Leaf(_) => 3,
+            Node::Inner { level, .. } => level,
+        }
+    }
+    /// The smallest and biggest coordinates of the live cells in a node, with y going down.
+    fn bounds(
+        &self,
+        index: usize,
+        memo: &mut HashMap<usize, Option<(i64, i64, i64, i64)>>,
+    ) -> Option<(i64, i64, i64, i64)> {
+        if index == 0 {
+            return None;
+        }
+        if let Some(bounds) = memo.get(&index) {
+            return *bounds;
+        }
+
+        let bounds = match self.node(index) {
+            Node::Leaf(bits) => (0..64).filter(|i| bits >> i & 1 == 1).fold(None, |b, i| {
+                let (x, y) = (i % 8, i / 8);
+                Some(merge(b, (x, y, x, y)))
+            }),
+            Node::Inner { level, children } => {
+                let half = 1 << (level - 1);
+                let offsets = [(0, 0), (half, 0), (0, half), (half, half)];
+
+                children
+                    .iter()
+                    .zip(offsets)
+                    .filter_map(|(child, (dx, dy))| {
+                        let (x0, y0, x1, y1) = self.bounds(*child, memo)?;
+                        Some((x0 + dx, y0 + dy, x1 + dx, y1 + dy))
+                    })
+                    .fold(None, |b, child| Some(merge(b, child)))
+            }
+        };
+
+        memo.insert(index, bounds);
+        bounds
+    }
+    /// Calls `f` for every live cell in a node, with y going down.
+    fn for_each_cell(&self, index: usize, x: i64, y: i64, f: &mut impl FnMut(i64, i64)) {
+        if index == 0 {
+            return;
+        }
+
+        match self.node(index) {
+            Node::Leaf(bits) => {
+                for i in (0..64).filter(|i| bits >> i & 1 == 1) {
+                    f(x + i % 8, y + i / 8);
+                }
+            }
+            Node::Inner { level, children } => {
+                let half = 1 << (level - 1);
+                let offsets = [(0, 0), (half, 0), (0, half), (half, half)];
+
+                for (child, (dx, dy)) in children.iter().zip(offsets) {
+                    self.for_each_cell(*child, x + dx, y + dy, f);
+                }
+            }
+        }
+    }
+    /// Expands the pattern into a grid that just fits it, as long as it's no bigger than
+    /// MAX_EXPANDED_SIZE.
+    pub fn to_grid(&self) -> Result<Grid<bool>, String> {
+        let root = self.nodes.len();
+        let Some((x0, y0, x1, y1)) = self.bounds(root, &mut HashMap::new()) else {
+            return Ok(Grid::new(0, 0));
+        };
+
+        let (width, height) = (x1 - x0 + 1, y1 - y0 + 1);
+        if width > MAX_EXPANDED_SIZE || height > MAX_EXPANDED_SIZE {
+            return Err(format!(
+                "The pattern is {}x{}, which is too big for the board (the limit is {}x{}).",
+                width, height, MAX_EXPANDED_SIZE, MAX_EXPANDED_SIZE
+            ));
+        }
+
+        // Grids on the board go bottom to top, so flip it.
+        let mut grid = Grid::new(height as usize, width as usize);
+        self.for_each_cell(root, -x0, -y0, &mut |x, y| {
+            grid[((height - 1 - y) as usize, x as usize)] = true;
+        });
+
+        Ok(grid)
+    }
+    /// Builds a quadtree from a grid, storing each distinct square only once.
+    pub fn from_grid(tiles: &Grid<bool>) -> MacroCell {
+        let size = tiles.cols().max(tiles.rows()).max(8).next_power_of_two();
+        let mut builder = Builder {
+            tiles,
+            nodes: Vec::new(),
+            index: HashMap::new(),
+        };
+
+        let root = builder.build(size.trailing_zeros(), 0, 0);
+        let mut nodes = builder.nodes;
+        if root == 0 {
+            nodes.push(Node::Leaf(0));
+        }
+
+        MacroCell {
+            nodes,
+            ..Default::default()
+        }
+    }
+}
+
+fn merge(a: Option<(i64, i64, i64, i64)>, b: (i64, i64, i64, i64)) -> (i64, i64, i64, i64) {
+    match a {
+        Some(a) => (a.0.min(b.0), a.1.min(b.1), a.2.max(b.2), a.3.max(b.3)),
+        None => b,
+    }
+}
+
+/// Used by MacroCell::from_grid to turn squares of the grid into nodes.
+struct Builder<'a> {
+    tiles: &'a Grid<bool>,
+    nodes: Vec<Node>,
+    index: HashMap<Node, usize>,
+}
+
+impl Builder<'_> {
+    /// Whether the cell at (x, y), with y going down from the top of the grid, is alive.
+    fn get(&self, x: usize, y: usize) -> bool {
+        let rows = self.tiles.rows();
+        y < rows && self.tiles.get(rows - 1 - y, x).copied().unwrap_or(false)
+    }
+    /// The node for the 2^level square with its top left at (x, y), or 0 if it's empty.
+    fn build(&mut self, level: u32, x: usize, y: usize) -> usize {
+        if x >= self.tiles.cols() || y >= self.tiles.rows() {
+            return 0;
+        }
+
+        let node = if level == 3 {
+            let mut bits = 0;
+            for i in 0..64 {
+                if self.get(x + i % 8, y + i / 8) {
+                    bits |= 1 << i;
+                }
+            }
+            if bits == 0 {
+                return 0;
+            }
+            Node::Leaf(bits)
+        } else {
+            let half = 1 << (level - 1);
+            let children = [
+                self.build(level - 1, x, y),
+                self.build(level - 1, x + half, y),
+                self.build(level - 1, x, y + half),
+                self.build(level - 1, x + half, y + half),
+            ];
+            if children == [0; 4] {
+                return 0;
+            }
+            Node::Inner { level, children }
+        };
+
+        if let Some(&index) = self.index.get(&node) {
+            return index;
+        }
+        self.nodes.push(node);
+        self.index.insert(node, self.nodes.len());
+        self.nodes.len()
+    }
+}
+
+/// Reads a macrocell file, expanding it.
+pub fn parse_macrocell(text: &str) -> Result<PatternFile, String> {
+    let macrocell = MacroCell::parse(text)?;
+
+    Ok(PatternFile {
+        tiles: macrocell.to_grid()?,
+        rule: macrocell.rule,
+        comments: macrocell.comments,
+        warnings: macrocell.warnings,
+        ..Default::default()
+    })
+}
+
+/// Writes a pattern as a macrocell file.
+pub fn to_macrocell(file: &PatternFile) -> String {
+    let mut macrocell = MacroCell::from_grid(&file.tiles);
+    macrocell.rule = file.rule;
+    macrocell.comments = file.comments.clone();
+    if let Some(name) = &file.name {
+        macrocell.comments.insert(0, name.clone());
+    }
+
+    macrocell.to_text()
+}
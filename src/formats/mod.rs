@@ -0,0 +1,203 @@
+//! Reading and writing pattern files from other programs (Golly, LifeWiki, etc.).
+//!
+//! Unlike savestates, these only store the pattern itself, cropped to fit.
+
+pub use image::*;
+pub use life::*;
+pub use macrocell::*;
+pub use plaintext::*;
+pub use raster::*;
+pub use recording::*;
+pub use rle::*;
+pub use svg::*;
+
+mod image;
+mod life;
+mod macrocell;
+mod plaintext;
+mod raster;
+mod recording;
+mod rle;
+mod svg;
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// The biggest pattern (in either direction) that can be read from a file. Anything bigger is
+/// probably a broken file, and would run out of memory.
+pub const MAX_PATTERN_SIZE: usize = 8192;
+
+/// Reads a rule written in a pattern file. Anything after a `:` (a bounded grid, like `:T100,100`)
+/// is ignored. Rules we can't run, like Generations rules or LifeHistory, are left out with a
+/// warning rather than failing the whole file.
+fn read_rule(rule: &mut Option<Rule>, warnings: &mut Vec<String>, text: &str) {
+    let text = text.split(':').next().unwrap_or("").trim();
+
+    match Rule::parse(text) {
+        Ok(parsed) => *rule = Some(parsed),
+        Err(_) => warnings.push(format!(
+            "Unknown rule '{}', keeping the current rule.",
+            text
+        )),
+    }
+}
+
+/// The pattern file formats we can read and write.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Rle,
+    /// .cells files.
+    Plaintext,
+    Life105,
+    Life106,
+    Macrocell,
+}
+
+impl Format {
+    /// Guesses the format from a file extension. Both Life formats use .lif, so those have to be
+    /// told apart by their contents.
+    pub fn from_extension(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "rle" => Some(Format::Rle),
+            "cells" => Some(Format::Plaintext),
+            "mc" => Some(Format::Macrocell),
+            _ => None,
+        }
+    }
+    /// Guesses the format from what's in the file.
+    pub fn detect(text: &str) -> Format {
+        let first = text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or("");
+
+        if first.starts_with("[M2]") {
+            Format::Macrocell
+        } else if first.starts_with("#Life 1.06") {
+            Format::Life106
+        } else if first.starts_with("#Life 1.05") {
+            Format::Life105
+        } else if first.starts_with('!') || first.starts_with(['.', 'O']) {
+            Format::Plaintext
+        } else {
+            Format::Rle
+        }
+    }
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Rle => "rle",
+            Format::Plaintext => "cells",
+            Format::Life105 | Format::Life106 => "lif",
+            Format::Macrocell => "mc",
+        }
+    }
+    pub fn parse(self, text: &str) -> Result<PatternFile, String> {
+        match self {
+            Format::Rle => parse_rle(text),
+            Format::Plaintext => parse_plaintext(text),
+            Format::Life105 => parse_life105(text),
+            Format::Life106 => parse_life106(text),
+            Format::Macrocell => parse_macrocell(text),
+        }
+    }
+    pub fn write(self, file: &PatternFile) -> String {
+        match self {
+            Format::Rle => to_rle(file),
+            Format::Plaintext => to_plaintext(file),
+            Format::Life105 => to_life105(file),
+            Format::Life106 => to_life106(file),
+            Format::Macrocell => to_macrocell(file),
+        }
+    }
+}
+
+/// A pattern read from, or to be written to, a pattern file.
+#[derive(Clone, Debug)]
+pub struct PatternFile {
+    /// The cells, bottom row first like the board.
+    pub tiles: Grid<bool>,
+    /// The rule the pattern is meant to run under, if the file says.
+    pub rule: Option<Rule>,
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub comments: Vec<String>,
+    /// Things in the file that were skipped over, to show to the user.
+    pub warnings: Vec<String>,
+}
+
+impl Default for PatternFile {
+    fn default() -> Self {
+        Self {
+            tiles: Grid::new(0, 0),
+            rule: None,
+            name: None,
+            author: None,
+            comments: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+impl PatternFile {
+    /// A pattern file for some cells, running under the current rule.
+    pub fn new(tiles: Grid<bool>) -> Self {
+        Self {
+            tiles,
+            rule: Some(current_rule()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Reads a pattern file, working out the format from the extension or the contents.
+pub fn read_pattern(path: impl AsRef<Path>) -> Result<PatternFile, String> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    Format::from_extension(path)
+        .unwrap_or_else(|| Format::detect(&text))
+        .parse(&text)
+}
+
+/// Reads a pattern file, or a savestate if there's no file at that path.
+pub fn read_pattern_or_savestate(path_or_name: &str) -> Result<PatternFile, String> {
+    if Path::new(path_or_name).is_file() {
+        read_pattern(path_or_name)
+    } else {
+        let savestate = load_savestate(path_or_name.to_string()).map_err(|e| e.to_string())?;
+        Ok(PatternFile {
+            tiles: savestate.tiles(),
+            rule: Some(savestate.metadata.rule),
+            ..Default::default()
+        })
+    }
+}
+
+/// Writes a pattern file, in the format that matches the extension (RLE if it doesn't match any).
+///
+/// .lif files are written as Life 1.06, unless Life 1.05 is the export format.
+pub fn write_pattern(path: impl AsRef<Path>, file: &PatternFile) -> Result<(), String> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+
+    let format = match path.extension().and_then(|e| e.to_str()) {
+        Some("lif" | "life") if CONFIG.export_format == Format::Life105 => Format::Life105,
+        Some("lif" | "life") => Format::Life106,
+        _ => Format::from_extension(path).unwrap_or(Format::Rle),
+    };
+
+    fs::write(path, format.write(file))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// The folder exported patterns go in.
+pub fn pattern_dir() -> String {
+    BASE_DIR.to_string() + "/patterns"
+}
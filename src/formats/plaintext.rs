@@ -0,0 +1,84 @@
+//! The plaintext format (.cells), one line per row.
+//!
+//! ```text
+//! !Name: Glider
+//! !A comment.
+//! .O.
+//! ..O
+//! OOO
+//! ```
+
+use super::PatternFile;
+use crate::prelude::*;
+
+/// Reads a plaintext file. `O` and `*` are alive, anything else is dead.
+pub fn parse_plaintext(text: &str) -> Result<PatternFile, String> {
+    let mut file = PatternFile::default();
+    let mut rows: Vec<Vec<bool>> = Vec::new();
+
+    for line in text.lines() {
+        let line = line.trim_end();
+
+        if let Some(comment) = line.strip_prefix('!') {
+            let comment = comment.trim();
+            if let Some(name) = comment.strip_prefix("Name:") {
+                file.name = Some(name.trim().to_string());
+            } else if let Some(author) = comment.strip_prefix("Author:") {
+                file.author = Some(author.trim().to_string());
+            } else {
+                file.comments.push(comment.to_string());
+            }
+            continue;
+        }
+
+        let row = line
+            .chars()
+            .map(|c| match c {
+                'O' | 'o' | '*' => Ok(true),
+                '.' | ' ' => Ok(false),
+                c => Err(format!("Unexpected '{}' in pattern.", c)),
+            })
+            .collect::<Result<Vec<bool>, String>>()?;
+        rows.push(row);
+    }
+
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let height = rows.len();
+
+    // Grids on the board go bottom to top, so flip it.
+    let mut tiles = Grid::new(height, width);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, alive) in row.iter().enumerate() {
+            tiles[(height - 1 - y, x)] = *alive;
+        }
+    }
+    file.tiles = tiles;
+
+    Ok(file)
+}
+
+/// Writes a pattern as plaintext. The format has no way to store a rule, so it's left out.
+pub fn to_plaintext(file: &PatternFile) -> String {
+    let tiles = &file.tiles;
+    let mut text = String::new();
+
+    if let Some(name) = &file.name {
+        text += &format!("!Name: {}\n", name);
+    }
+    if let Some(author) = &file.author {
+        text += &format!("!Author: {}\n", author);
+    }
+    for comment in &file.comments {
+        text += &format!("!{}\n", comment);
+    }
+
+    for row in (0..tiles.rows()).rev() {
+        let line: String = (0..tiles.cols())
+            .map(|x| if tiles[(row, x)] { 'O' } else { '.' })
+            .collect();
+        text += line.trim_end_matches('.');
+        text.push('\n');
+    }
+
+    text
+}
@@ -0,0 +1,107 @@
+//! Drawing cells into images on the CPU, so it works without a window or a GPU.
+//!
+//! Uses the colors from the config. Each cell is `scale` pixels across, and grid lines (when they
+//! fit, at a scale of 3 or more) are one pixel wide and drawn over the edges of the cells.
+//!
+//! Big boards can be shrunk with `cells_per_pixel`, where each pixel covers a square of cells and
+//! is alive if any of them are. There are no grid lines when shrinking.
+
+use crate::prelude::*;
+use nannou::image::{Rgb, RgbImage};
+use std::fs;
+use std::path::Path;
+
+/// How to draw cells into an image.
+#[derive(Clone, Copy, Debug)]
+pub struct RasterOptions {
+    /// Pixels per cell.
+    pub scale: u32,
+    /// Cells per pixel (or per `scale` pixels), for shrinking big boards.
+    pub cells_per_pixel: u32,
+    pub grid: bool,
+}
+
+impl RasterOptions {
+    pub fn new(scale: u32, grid: bool) -> Self {
+        Self {
+            scale: scale.max(1),
+            cells_per_pixel: 1,
+            grid,
+        }
+    }
+    pub fn cells_per_pixel(mut self, cells_per_pixel: u32) -> Self {
+        self.cells_per_pixel = cells_per_pixel.max(1);
+        self
+    }
+    fn grid_lines(&self) -> bool {
+        self.grid && self.scale >= 3 && self.cells_per_pixel == 1
+    }
+}
+
+/// Draws some cells into an image, top row first.
+pub fn rasterize(tiles: &Grid<bool>, options: &RasterOptions) -> Result<RgbImage, String> {
+    let scale = options.scale;
+    let shrink = options.cells_per_pixel as usize;
+    let grid = options.grid_lines();
+    let (cols, rows) = (tiles.cols().div_ceil(shrink), tiles.rows().div_ceil(shrink));
+    // With grid lines there's an extra line along the right and top edges.
+    let size = |cells: usize| {
+        u32::try_from(cells)
+            .ok()?
+            .checked_mul(scale)?
+            .checked_add(grid as u32)
+    };
+    let (Some(width), Some(height)) = (size(cols), size(rows)) else {
+        return Err("The image would be too big.".to_string());
+    };
+    let (cols, rows) = (cols as u32, rows as u32);
+
+    let [cell, background, grid_color] = [
+        CONFIG.cell_color,
+        CONFIG.background_color,
+        CONFIG.grid_color,
+    ]
+    .map(|color| Rgb(color.to_rgb8()));
+
+    let image = RgbImage::from_fn(width.max(1), height.max(1), |px, py| {
+        let (x, y) = (px / scale, py / scale);
+        if grid && (px % scale == 0 || py % scale == 0) {
+            return grid_color;
+        }
+        if x >= cols || y >= rows {
+            return background;
+        }
+
+        // The square of cells under this pixel, counting up from the bottom.
+        let (x, y) = (x as usize * shrink, (rows - 1 - y) as usize * shrink);
+        let alive = (y..(y + shrink).min(tiles.rows()))
+            .any(|y| (x..(x + shrink).min(tiles.cols())).any(|x| tiles[(y, x)]));
+        match alive {
+            true => cell,
+            false => background,
+        }
+    });
+
+    Ok(image)
+}
+
+/// Draws some cells into a PNG file.
+pub fn write_png(
+    path: impl AsRef<Path>,
+    tiles: &Grid<bool>,
+    options: &RasterOptions,
+) -> Result<(), String> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+
+    rasterize(tiles, options)?
+        .save(path)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// The folder exported images go in.
+pub fn image_dir() -> String {
+    BASE_DIR.to_string() + "/images"
+}
@@ -0,0 +1,248 @@
+//! Recording a run of generations as an animated GIF, or as numbered PNG frames (for ffmpeg and
+//! the like).
+//!
+//! The output is a GIF if the path ends in .gif, otherwise it's a folder of frame_00000.png,
+//! frame_00001.png, etc.
+//!
+//! Frames are kept as cells, packed the same way as in savestates, while recording. They're only
+//! drawn when the recording is saved, which the game does in the background.
+
+use crate::prelude::*;
+use nannou::image::codecs::gif::{GifEncoder, Repeat};
+use nannou::image::{Delay, DynamicImage, Frame};
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::Path;
+
+/// Recordings stop taking frames after this many, so they can't eat all the memory.
+pub const MAX_FRAMES: usize = 5000;
+
+/// Options for recording from the command line.
+#[derive(clap::Args, Debug)]
+pub struct RecordOptions {
+    /// A pattern file, or the name of a savestate.
+    pub input: String,
+
+    /// A .gif file, or a folder to put the PNG frames in.
+    pub output: String,
+
+    /// How many generations to run for.
+    #[arg(short = 'n', long, default_value_t = 100)]
+    pub generations: usize,
+
+    /// Only keep every Nth generation.
+    #[arg(long, default_value_t = 1)]
+    pub stride: usize,
+
+    /// Pixels per cell.
+    #[arg(short, long, default_value_t = 4)]
+    pub scale: u32,
+
+    /// Cells per pixel, for shrinking big patterns.
+    #[arg(long, default_value_t = 1)]
+    pub cells_per_pixel: u32,
+
+    /// Draw grid lines between the cells (needs a scale of at least 3).
+    #[arg(short, long)]
+    pub grid: bool,
+
+    /// Empty cells to add around the pattern, so it has room to grow.
+    #[arg(long, default_value_t = 16)]
+    pub margin: usize,
+
+    /// Only record this part of the board, as "x,y,width,height" from the bottom left.
+    #[arg(long, value_parser = parse_region)]
+    pub crop: Option<Region>,
+
+    /// Milliseconds between frames in a GIF.
+    #[arg(long, default_value_t = 100)]
+    pub delay: u32,
+}
+
+/// A rectangle of the board: x, y (from the bottom left), width and height.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region(pub usize, pub usize, pub usize, pub usize);
+
+impl Region {
+    /// The cells of the board inside the region. Anything off the board is dead.
+    pub fn tiles(&self, tiles: &Grid<bool>) -> Grid<bool> {
+        let Region(x0, y0, width, height) = *self;
+
+        let mut region = Grid::new(height, width);
+        for ((y, x), tile) in region.indexed_iter_mut() {
+            *tile = tiles.get(y0 + y, x0 + x).copied().unwrap_or(false);
+        }
+
+        region
+    }
+}
+
+fn parse_region(text: &str) -> Result<Region, String> {
+    let numbers = text
+        .split(',')
+        .map(|n| n.trim().parse::<usize>())
+        .collect::<Result<Vec<usize>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    match numbers.as_slice() {
+        &[x, y, width, height] => Ok(Region(x, y, width, height)),
+        _ => Err("Expected x,y,width,height.".to_string()),
+    }
+}
+
+/// The cells of one frame.
+#[derive(Clone, Debug)]
+struct PackedFrame {
+    encoding: Encoding,
+    bytes: Vec<u8>,
+    width: usize,
+    height: usize,
+}
+
+impl PackedFrame {
+    fn pack(tiles: &Grid<bool>) -> Self {
+        let encoding = Encoding::choose(tiles);
+        Self {
+            encoding,
+            bytes: encoding.encode(tiles),
+            width: tiles.cols(),
+            height: tiles.rows(),
+        }
+    }
+    fn unpack(&self) -> Result<Grid<bool>, String> {
+        self.encoding.decode(&self.bytes, self.width, self.height)
+    }
+}
+
+/// Collects frames of a run, then writes them out.
+#[derive(Clone, Debug)]
+pub struct Recorder {
+    frames: Vec<PackedFrame>,
+    raster: RasterOptions,
+    stride: usize,
+    crop: Option<Region>,
+    delay: u32,
+    /// Generations seen so far, including skipped ones.
+    generations: usize,
+}
+
+impl Recorder {
+    pub fn new(raster: RasterOptions, stride: usize, crop: Option<Region>, delay: u32) -> Self {
+        Self {
+            frames: Vec::new(),
+            raster,
+            stride: stride.max(1),
+            crop,
+            delay,
+            generations: 0,
+        }
+    }
+    /// Called with every generation, keeps every `stride`th one. Returns false once the recording
+    /// is full.
+    pub fn capture(&mut self, tiles: &Grid<bool>) -> bool {
+        if self.frames.len() >= MAX_FRAMES {
+            return false;
+        }
+
+        if self.generations.is_multiple_of(self.stride) {
+            let frame = match self.crop {
+                Some(region) => PackedFrame::pack(&region.tiles(tiles)),
+                None => PackedFrame::pack(tiles),
+            };
+            self.frames.push(frame);
+        }
+        self.generations += 1;
+
+        true
+    }
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+    /// Draws the frames, then writes them as a GIF if the path ends in .gif, otherwise as PNGs
+    /// in a folder.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let error =
+            |e: &dyn std::fmt::Display| format!("Failed to write {}: {}", path.display(), e);
+        let images = self
+            .frames
+            .iter()
+            .map(|frame| rasterize(&frame.unpack()?, &self.raster));
+
+        if path.extension().is_some_and(|e| e == "gif") {
+            if let Some(dir) = path.parent() {
+                let _ = fs::create_dir_all(dir);
+            }
+
+            let file = File::create(path).map_err(|e| error(&e))?;
+            let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), 30);
+            encoder
+                .set_repeat(Repeat::Infinite)
+                .map_err(|e| error(&e))?;
+
+            let delay = Delay::from_numer_denom_ms(self.delay, 1);
+            for image in images {
+                let rgba = DynamicImage::ImageRgb8(image?).into_rgba8();
+                encoder
+                    .encode_frame(Frame::from_parts(rgba, 0, 0, delay))
+                    .map_err(|e| error(&e))?;
+            }
+        } else {
+            fs::create_dir_all(path).map_err(|e| error(&e))?;
+            for (i, image) in images.enumerate() {
+                let frame = path.join(format!("frame_{:05}.png", i));
+                image?.save(&frame).map_err(|e| error(&e))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs a recording from the command line.
+pub fn record(options: RecordOptions) {
+    let file = match read_pattern_or_savestate(&options.input) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    for warning in &file.warnings {
+        eprintln!("{}", warning);
+    }
+    if let Some(rule) = file.rule {
+        set_rule(rule);
+    }
+
+    let margin = options.margin * 2;
+    let mut board = Board { tiles: file.tiles };
+    board.set_wh(board.width() + margin, board.height() + margin);
+
+    let mut recorder = Recorder::new(
+        RasterOptions::new(options.scale, options.grid).cells_per_pixel(options.cells_per_pixel),
+        options.stride,
+        options.crop,
+        options.delay,
+    );
+    recorder.capture(&board.tiles);
+    for _ in 0..options.generations {
+        board.advance();
+        if !recorder.capture(&board.tiles) {
+            eprintln!("Stopped at {} frames.", MAX_FRAMES);
+            break;
+        }
+    }
+
+    match recorder.save(&options.output) {
+        Ok(()) => println!(
+            "Recorded {} frames to {}.",
+            recorder.frame_count(),
+            options.output
+        ),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! The RLE format, used by Golly and LifeWiki.
+//!
+//! ```text
+//! #N Glider
+//! #C A comment.
+//! x = 3, y = 3, rule = B3/S23
+//! bob$2bo$3o!
+//! ```
+//!
+//! Runs of cells are written as a count and a letter: `b` (or `.`) is dead, anything else is alive.
+//! Multi-state letters (`A`-`X`, optionally after a `p`-`y` prefix) are all treated as alive, since
+//! the board only has two states. `$` ends a row, and `!` ends the pattern.
+
+use super::{read_rule, PatternFile};
+use crate::prelude::*;
+
+/// Longest line the writer will produce, which is what Golly uses.
+const LINE_LENGTH: usize = 70;
+
+/// Reads an RLE file. Patterns bigger than MAX_PATTERN_SIZE either way are an error.
+pub fn parse_rle(text: &str) -> Result<PatternFile, String> {
+    let max = MAX_PATTERN_SIZE;
+    let too_big = || format!("The pattern is bigger than {0}x{0}.", max);
+
+    let mut file = PatternFile::default();
+    let mut header_size = (0, 0);
+    let mut lines = text.lines().map(str::trim).peekable();
+
+    // Comments and the header.
+    while let Some(line) = lines.peek() {
+        if let Some(comment) = line.strip_prefix('#') {
+            let (kind, rest) = comment.split_at(comment.len().min(1));
+            let rest = rest.trim().to_string();
+
+            match kind {
+                "N" => file.name = Some(rest),
+                "O" => file.author = Some(rest),
+                "r" => read_rule(&mut file.rule, &mut file.warnings, &rest),
+                _ => file.comments.push(rest),
+            }
+        } else if line.starts_with('x') {
+            // The rule goes last and can have commas in it (like B3/S23:P30,20), so it's split off
+            // before the rest.
+            let (size, rule) = match line.split_once("rule") {
+                Some((size, rule)) => (size, Some(rule.trim_start().trim_start_matches('='))),
+                None => (*line, None),
+            };
+            if let Some(rule) = rule {
+                read_rule(&mut file.rule, &mut file.warnings, rule);
+            }
+
+            for part in size.split(',') {
+                let Some((key, value)) = part.split_once('=') else {
+                    continue;
+                };
+                let value = value.trim();
+
+                match key.trim() {
+                    "x" => header_size.0 = value.parse().map_err(|_| "Invalid width in header.")?,
+                    "y" => {
+                        header_size.1 = value.parse().map_err(|_| "Invalid height in header.")?
+                    }
+                    _ => (),
+                }
+            }
+            if header_size.0 > max || header_size.1 > max {
+                return Err(too_big());
+            }
+        } else if !line.is_empty() {
+            break;
+        }
+        lines.next();
+    }
+
+    // The cells, going top to bottom.
+    let mut cells = Vec::new();
+    let (mut x, mut y) = (0, 0);
+    let mut count = String::new();
+    let advance = |pos: usize, run: usize| {
+        pos.checked_add(run)
+            .filter(|pos| *pos <= max)
+            .ok_or_else(too_big)
+    };
+
+    'outer: for line in lines {
+        let mut chars = line.chars();
+
+        while let Some(c) = chars.next() {
+            if c.is_ascii_digit() {
+                count.push(c);
+                continue;
+            }
+
+            let run: usize = if count.is_empty() {
+                1
+            } else {
+                count.parse().map_err(|_| "Invalid run count.")?
+            };
+            count.clear();
+
+            match c {
+                'b' | '.' => x = advance(x, run)?,
+                '$' => {
+                    x = 0;
+                    y = advance(y, run)?;
+                }
+                '!' => break 'outer,
+                'p'..='y' => {
+                    // The first half of a two letter state, the second letter says which.
+                    match chars.next() {
+                        Some('A'..='X') => (),
+                        _ => return Err(format!("Invalid state after '{}'.", c)),
+                    }
+                    let end = advance(x, run)?;
+                    cells.extend((x..end).map(|x| (x, y)));
+                    x = end;
+                }
+                'o' | 'A'..='X' => {
+                    let end = advance(x, run)?;
+                    cells.extend((x..end).map(|x| (x, y)));
+                    x = end;
+                }
+                c if c.is_whitespace() => (),
+                c => return Err(format!("Unexpected '{}' in pattern.", c)),
+            }
+        }
+    }
+
+    let width = cells
+        .iter()
+        .map(|(x, _)| x + 1)
+        .max()
+        .unwrap_or(0)
+        .max(header_size.0);
+    let height = cells
+        .iter()
+        .map(|(_, y)| y + 1)
+        .max()
+        .unwrap_or(0)
+        .max(header_size.1);
+    if width > max || height > max {
+        return Err(too_big());
+    }
+
+    // Grids on the board go bottom to top, so flip it.
+    let mut tiles = Grid::new(height, width);
+    for (x, y) in cells {
+        tiles[(height - 1 - y, x)] = true;
+    }
+    file.tiles = tiles;
+
+    Ok(file)
+}
+
+/// Writes a pattern as RLE.
+pub fn to_rle(file: &PatternFile) -> String {
+    let tiles = &file.tiles;
+    let mut text = String::new();
+
+    if let Some(name) = &file.name {
+        text += &format!("#N {}\n", name);
+    }
+    if let Some(author) = &file.author {
+        text += &format!("#O {}\n", author);
+    }
+    for comment in &file.comments {
+        text += &format!("#C {}\n", comment);
+    }
+
+    text += &format!("x = {}, y = {}", tiles.cols(), tiles.rows());
+    if let Some(rule) = file.rule {
+        text += &format!(", rule = {}", rule.to_bs());
+    }
+    text.push('\n');
+
+    // Each item is a count and a letter. Empty rows are added to the next $, and dead cells at the
+    // end of a row are left out.
+    fn push(runs: &mut Vec<(usize, char)>, count: usize, c: char) {
+        match runs.last_mut() {
+            Some((n, last)) if *last == c => *n += count,
+            _ => runs.push((count, c)),
+        }
+    }
+
+    let mut runs: Vec<(usize, char)> = Vec::new();
+
+    for row in (0..tiles.rows()).rev() {
+        let mut row_runs: Vec<(usize, char)> = Vec::new();
+        for x in 0..tiles.cols() {
+            push(&mut row_runs, 1, if tiles[(row, x)] { 'o' } else { 'b' });
+        }
+        if row_runs.last().is_some_and(|(_, c)| *c == 'b') {
+            row_runs.pop();
+        }
+
+        for (count, c) in row_runs {
+            push(&mut runs, count, c);
+        }
+        push(&mut runs, 1, '$');
+    }
+
+    if runs.last().is_some_and(|(_, c)| *c == '$') {
+        runs.pop();
+    }
+
+    let mut line = String::new();
+    for (count, c) in runs {
+        let item = match count {
+            1 => c.to_string(),
+            n => format!("{}{}", n, c),
+        };
+        if line.len() + item.len() > LINE_LENGTH {
+            text += &line;
+            text.push('\n');
+            line.clear();
+        }
+        line += &item;
+    }
+    text += &line;
+    text += "!\n";
+
+    text
+}
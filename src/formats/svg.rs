@@ -0,0 +1,211 @@
+//! Drawing cells into SVG files, for figures that need to stay sharp at any size.
+//!
+//! Neighbouring live cells are merged into rectangles (runs along each row, joined with the same
+//! run in the rows below), so big patterns don't turn into one element per cell.
+
+use crate::prelude::*;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// How to draw cells into an SVG.
+#[derive(Clone, Copy, Debug)]
+pub struct SvgOptions {
+    /// Units per cell.
+    pub scale: u32,
+    pub grid: bool,
+    /// Number the rows and columns along the left and bottom edges.
+    pub labels: bool,
+    /// The board coordinates of the bottom left cell, for the labels.
+    pub origin: (usize, usize),
+}
+
+impl SvgOptions {
+    pub fn new(scale: u32, grid: bool, labels: bool) -> Self {
+        Self {
+            scale: scale.max(1),
+            grid,
+            labels,
+            origin: (0, 0),
+        }
+    }
+    pub fn origin(self, x: usize, y: usize) -> Self {
+        Self {
+            origin: (x, y),
+            ..self
+        }
+    }
+}
+
+/// A rectangle of live cells, in cells from the top left.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Covers the live cells with as few rectangles as the row-by-row merging finds, top row first.
+pub fn merge_rects(tiles: &Grid<bool>) -> Vec<CellRect> {
+    let (cols, rows) = (tiles.cols(), tiles.rows());
+    let mut rects = Vec::new();
+    // Runs that are still growing downwards: (start, end) to the row they started on.
+    let mut open: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+
+    for y in 0..=rows {
+        let mut runs = Vec::new();
+        if y < rows {
+            // Images go top to bottom, the board goes bottom to top.
+            let row = rows - 1 - y;
+            let mut x = 0;
+            while x < cols {
+                if tiles[(row, x)] {
+                    let start = x;
+                    while x < cols && tiles[(row, x)] {
+                        x += 1;
+                    }
+                    runs.push((start, x));
+                }
+                x += 1;
+            }
+        }
+
+        let mut still_open = BTreeMap::new();
+        for run in runs {
+            still_open.insert(run, open.remove(&run).unwrap_or(y));
+        }
+        for ((start, end), top) in open {
+            rects.push(CellRect {
+                x: start,
+                y: top,
+                width: end - start,
+                height: y - top,
+            });
+        }
+        open = still_open;
+    }
+
+    rects
+}
+
+/// Draws some cells into an SVG document.
+pub fn to_svg(tiles: &Grid<bool>, options: &SvgOptions) -> String {
+    let scale = options.scale as usize;
+    let (cols, rows) = (tiles.cols(), tiles.rows());
+    let (width, height) = (cols * scale, rows * scale);
+
+    let font_size = (scale * 4 / 5).max(6);
+    // Room for the labels on the left and bottom.
+    let (left, bottom) = match options.labels {
+        true => (font_size * 3, font_size * 2),
+        false => (0, 0),
+    };
+
+    let [cell, background, grid_color] = [
+        CONFIG.cell_color,
+        CONFIG.background_color,
+        CONFIG.grid_color,
+    ]
+    .map(|color| color.to_hex());
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = left + width,
+        h = height + bottom,
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="100%" height="100%" fill="{}"/>"#,
+        background
+    );
+
+    let _ = writeln!(
+        svg,
+        r#"<g transform="translate({} 0)" fill="{}" shape-rendering="crispEdges">"#,
+        left, cell
+    );
+    for rect in merge_rects(tiles) {
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}"/>"#,
+            rect.x * scale,
+            rect.y * scale,
+            rect.width * scale,
+            rect.height * scale
+        );
+    }
+    if options.grid {
+        let mut path = String::new();
+        for x in 0..=cols {
+            let _ = write!(path, "M{} 0V{}", x * scale, height);
+        }
+        for y in 0..=rows {
+            let _ = write!(path, "M0 {}H{}", y * scale, width);
+        }
+        let _ = writeln!(
+            svg,
+            r#"<path d="{}" fill="none" stroke="{}" stroke-width="1"/>"#,
+            path, grid_color
+        );
+    }
+    svg += "</g>\n";
+
+    if options.labels {
+        // Label every 1, 2, 5, 10, 20, 50... cells, whichever is the first that leaves room.
+        let step = [1, 2, 5]
+            .into_iter()
+            .cycle()
+            .zip((0..).map(|i| 10usize.pow(i / 3)))
+            .map(|(step, power)| step * power)
+            .find(|step| step * scale >= font_size * 3)
+            .unwrap();
+        let (origin_x, origin_y) = options.origin;
+
+        let _ = writeln!(
+            svg,
+            r#"<g fill="{}" font-family="monospace" font-size="{}">"#,
+            cell, font_size
+        );
+        for x in (0..cols).filter(|x| (origin_x + x) % step == 0) {
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+                left + x * scale + scale / 2,
+                height + font_size * 3 / 2,
+                origin_x + x
+            );
+        }
+        for y in (0..rows).filter(|y| (origin_y + y) % step == 0) {
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}" text-anchor="end" dominant-baseline="middle">{}</text>"#,
+                left - font_size / 2,
+                (rows - 1 - y) * scale + scale / 2,
+                origin_y + y
+            );
+        }
+        svg += "</g>\n";
+    }
+
+    svg += "</svg>\n";
+    svg
+}
+
+/// Draws some cells into an SVG file.
+pub fn write_svg(
+    path: impl AsRef<Path>,
+    tiles: &Grid<bool>,
+    options: &SvgOptions,
+) -> Result<(), String> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+
+    fs::write(path, to_svg(tiles, options))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
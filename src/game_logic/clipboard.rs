@@ -0,0 +1,107 @@
+//! The clipboard history, and named clips.
+//!
+//! Everything put in the clipboard is also added to the front of the history, which keeps the
+//! last CONFIG.clipboard_history clips. The number keys put one back in the clipboard, 1 being the
+//! most recent.
+//!
+//! Clips can also be given a name with Ctrl+Shift+C, which saves them as RLE files in
+//! BASE_DIR/clips. Those show up in the pattern library (Ctrl+L), so they're kept between
+//! sessions.
+
+use crate::prelude::*;
+use std::collections::VecDeque;
+use std::path::Path;
+
+/// The last few things put in the clipboard, most recent first.
+#[derive(Clone, Debug, Default)]
+pub struct ClipboardHistory {
+    clips: VecDeque<Grid<bool>>,
+}
+
+impl ClipboardHistory {
+    /// Adds a clip to the front, moving it there if it's already in the history.
+    pub fn push(&mut self, tiles: Grid<bool>, max: usize) {
+        self.clips.retain(|clip| *clip != tiles);
+        self.clips.push_front(tiles);
+        self.clips.truncate(max.max(1));
+    }
+    /// The nth most recent clip, starting from 1.
+    pub fn get(&self, n: usize) -> Option<&Grid<bool>> {
+        self.clips.get(n.checked_sub(1)?)
+    }
+    pub fn len(&self) -> usize {
+        self.clips.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.clips.is_empty()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &Grid<bool>> {
+        self.clips.iter()
+    }
+}
+
+/// Puts some tiles in the clipboard, and adds them to the history.
+pub fn set_clipboard(model: &mut Model, tiles: Grid<bool>) {
+    model
+        .clipboard_history
+        .push(tiles.clone(), CONFIG.clipboard_history);
+    model.clipboard = Some(tiles);
+}
+
+/// Puts the nth most recent clip back in the clipboard.
+pub fn pick_clip(model: &mut Model, n: usize) {
+    match model.clipboard_history.get(n) {
+        Some(clip) => {
+            notify(format!(
+                "Clip {} ({}x{}) is in the clipboard.",
+                n,
+                clip.cols(),
+                clip.rows()
+            ));
+            model.clipboard = Some(clip.clone());
+        }
+        None => notify_error(format!("There's no clip {} in the history.", n)),
+    }
+}
+
+/// Which clip a number key picks.
+pub fn clip_number(key: Key) -> Option<usize> {
+    let keys = [
+        Key::Key1,
+        Key::Key2,
+        Key::Key3,
+        Key::Key4,
+        Key::Key5,
+        Key::Key6,
+        Key::Key7,
+        Key::Key8,
+        Key::Key9,
+    ];
+
+    keys.iter().position(|k| *k == key).map(|i| i + 1)
+}
+
+/// Saves some tiles as a named clip in `dir` (normally clip_dir()), without overwriting an
+/// existing one.
+pub fn save_clip(dir: &str, name: &str, tiles: &Grid<bool>) -> Result<(), String> {
+    check_name(name).map_err(|e| e.to_string())?;
+    let path = clip_path(dir, name);
+    if Path::new(&path).exists() {
+        return Err(format!("There's already a clip called '{}'.", name));
+    }
+
+    let file = PatternFile {
+        name: Some(name.to_string()),
+        ..PatternFile::new(tiles.clone())
+    };
+    write_pattern(path, &file)
+}
+
+/// The folder named clips are saved in.
+pub fn clip_dir() -> String {
+    BASE_DIR.to_string() + "/clips"
+}
+
+fn clip_path(dir: &str, name: &str) -> String {
+    format!("{}/{}.rle", dir, name)
+}
@@ -0,0 +1,140 @@
+//! Reading and writing pattern files and images from the window. Anything that goes wrong is shown
+//! as a notification, rather than returned.
+
+use crate::prelude::*;
+use std::path::Path;
+
+/// Switches to a pattern file's rule, after showing anything that was skipped while reading it.
+pub fn apply_file_rule(file: &PatternFile) {
+    for warning in &file.warnings {
+        notify_error(warning);
+    }
+    apply_pattern_rule(file.rule);
+}
+
+/// Reads a pattern file into the clipboard, ready to paste.
+pub fn load_pattern_to_clipboard(model: &mut Model, path: impl AsRef<Path>) {
+    match read_pattern(path) {
+        Ok(file) => {
+            apply_file_rule(&file);
+            set_clipboard(model, file.tiles);
+            notify_info("Pattern copied to the clipboard, press Ctrl+V to paste it.");
+        }
+        Err(e) => notify_error(e),
+    }
+}
+
+/// Turns an image into cells in the clipboard, ready to paste.
+pub fn load_image_to_clipboard(model: &mut Model, path: impl AsRef<Path>) {
+    let options = ImageImport::from_config(CONFIG.image_fit_board.then(|| model.board.wh()));
+
+    match import_image(path, &options) {
+        Ok(tiles) => {
+            notify_info(format!(
+                "Image copied to the clipboard ({}x{}, {} dithering), press Ctrl+V to paste it.",
+                tiles.cols(),
+                tiles.rows(),
+                options.dither
+            ));
+            set_clipboard(model, tiles);
+        }
+        Err(e) => notify_error(e),
+    }
+}
+
+/// Writes some tiles to a new file in the patterns folder, in CONFIG.export_format.
+pub fn export_pattern(tiles: Grid<bool>) {
+    let mut board = Board { tiles };
+    board.crop();
+
+    let format = CONFIG.export_format;
+    let name = unique_name(&pattern_dir(), &timestamp_id(), format.extension());
+    let path = format!("{}/{}.{}", pattern_dir(), name, format.extension());
+    match write_pattern(&path, &PatternFile::new(board.tiles)) {
+        Ok(()) => notify_info(format!("Pattern exported to {}.", path)),
+        Err(e) => notify_error(e),
+    }
+}
+
+/// Writes some tiles to a new PNG in the images folder, at CONFIG.image_export_scale (and
+/// CONFIG.image_export_cells_per_pixel).
+pub fn export_image(tiles: &Grid<bool>, grid: bool) {
+    let name = unique_name(&image_dir(), &timestamp_id(), "png");
+    let path = format!("{}/{}.png", image_dir(), name);
+    let options = RasterOptions::new(CONFIG.image_export_scale, grid)
+        .cells_per_pixel(CONFIG.image_export_cells_per_pixel);
+
+    match write_png(&path, tiles, &options) {
+        Ok(()) => notify_info(format!("Image exported to {}.", path)),
+        Err(e) => notify_error(e),
+    }
+}
+
+/// Writes some tiles to a new SVG in the images folder. `origin` is where the bottom left cell is
+/// on the board, for the coordinate labels.
+pub fn export_svg(tiles: &Grid<bool>, origin: (usize, usize), grid: bool) {
+    let name = unique_name(&image_dir(), &timestamp_id(), "svg");
+    let path = format!("{}/{}.svg", image_dir(), name);
+    let options =
+        SvgOptions::new(CONFIG.svg_scale, grid, CONFIG.svg_labels).origin(origin.0, origin.1);
+
+    match write_svg(&path, tiles, &options) {
+        Ok(()) => notify_info(format!("SVG exported to {}.", path)),
+        Err(e) => notify_error(e),
+    }
+}
+
+/// Starts recording the board (or just the selection) as it runs, including the current
+/// generation.
+pub fn start_recording(model: &mut Model) {
+    let crop = model.selection.as_ref().map(|selection| {
+        let (x, y) = (
+            selection.start.x.min(selection.end.x),
+            selection.start.y.min(selection.end.y),
+        );
+        Region(x, y, selection.width() + 1, selection.height() + 1)
+    });
+
+    let mut recorder = Recorder::new(
+        RasterOptions::new(CONFIG.image_export_scale, model.grid_lines)
+            .cells_per_pixel(CONFIG.image_export_cells_per_pixel),
+        CONFIG.record_stride,
+        crop,
+        CONFIG.record_delay,
+    );
+    recorder.capture(&model.board.tiles);
+    model.recorder = Some(recorder);
+
+    notify("Recording, press Ctrl+G again to stop.");
+}
+
+/// Stops recording, and writes the recording to the images folder in the background.
+pub fn stop_recording(model: &mut Model) {
+    let Some(recorder) = model.recorder.take() else {
+        return;
+    };
+
+    let path = match CONFIG.record_frames {
+        true => format!(
+            "{}/{}",
+            image_dir(),
+            unique_name(&image_dir(), &timestamp_id(), "")
+        ),
+        false => {
+            let name = unique_name(&image_dir(), &timestamp_id(), "gif");
+            format!("{}/{}.gif", image_dir(), name)
+        }
+    };
+
+    let job = Job::spawn("saving the recording", move |_| {
+        match recorder.save(&path) {
+            Ok(()) => JobOutput::Info(format!(
+                "Recorded {} frames to {}.",
+                recorder.frame_count(),
+                path
+            )),
+            Err(e) => JobOutput::Error(e),
+        }
+    });
+    start_job(model, job);
+}
@@ -0,0 +1,115 @@
+//! Background jobs, for slow things (like searches) that would freeze the window.
+//!
+//! A job runs on its own thread. When it's done it leaves a JobOutput behind, which `update` picks
+//! up and applies to the model.
+
+use crate::prelude::*;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// What a finished job wants done.
+pub enum JobOutput {
+    /// Put a pattern in the clipboard, and show a message.
+    Clipboard(Grid<bool>, String),
+    Info(String),
+    Error(String),
+    /// Nothing to show, like when a background save worked.
+    Done,
+}
+
+/// A handle to a job running on another thread.
+#[derive(Clone)]
+pub struct Job {
+    pub name: String,
+    cancel: Arc<AtomicBool>,
+    output: Arc<Mutex<Option<JobOutput>>>,
+}
+
+impl Job {
+    /// Starts running `work` on a new thread. `work` should check the flag it's given every now
+    /// and then, and stop early if it's set. If it panics, the job finishes with an error.
+    pub fn spawn(
+        name: impl Into<String>,
+        work: impl FnOnce(&AtomicBool) -> JobOutput + Send + 'static,
+    ) -> Self {
+        let job = Self {
+            name: name.into(),
+            cancel: Arc::new(AtomicBool::new(false)),
+            output: Arc::new(Mutex::new(None)),
+        };
+
+        let cancel = job.cancel.clone();
+        let output = job.output.clone();
+        let name = job.name.clone();
+        thread::spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| work(&cancel)));
+            *output.lock().unwrap() = Some(result.unwrap_or_else(|payload| {
+                JobOutput::Error(format!(
+                    "Job '{}' crashed: {}",
+                    name,
+                    panic_message(&*payload)
+                ))
+            }));
+        });
+
+        job
+    }
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+    fn take_output(&self) -> Option<JobOutput> {
+        self.output.lock().unwrap().take()
+    }
+}
+
+/// The message a thread panicked with.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => payload
+            .downcast_ref::<String>()
+            .cloned()
+            .unwrap_or_default(),
+    }
+}
+
+/// Starts a job, and lets the user know it's running.
+pub fn start_job(model: &mut Model, job: Job) {
+    notify(format!("Started {}.", job.name));
+    model.jobs.push(job);
+}
+
+/// Cancels every running job.
+pub fn cancel_jobs(model: &mut Model) {
+    for job in &model.jobs {
+        job.cancel();
+    }
+}
+
+/// Applies the output of any jobs that have finished. Called every frame.
+pub fn poll_jobs(model: &mut Model) {
+    let mut finished = Vec::new();
+
+    for (i, job) in model.jobs.iter().enumerate() {
+        if let Some(output) = job.take_output() {
+            finished.push((i, output));
+        }
+    }
+
+    for (i, output) in finished.into_iter().rev() {
+        model.jobs.remove(i);
+
+        match output {
+            JobOutput::Clipboard(grid, message) => {
+                set_clipboard(model, grid);
+                notify_info(message);
+            }
+            JobOutput::Info(message) => notify_info(message),
+            JobOutput::Error(message) => notify_error(message),
+            JobOutput::Done => (),
+        }
+    }
+}
@@ -6,6 +6,35 @@
 
 use crate::prelude::*;
 
+/// Tells the user which seed a soup was made with, so they can make it again.
+pub fn notify_soup(model: &Model, seed: u64) {
+    notify_info(format!(
+        "Randomized with seed {} ({}% density, {} symmetry).",
+        seed,
+        (CONFIG.soup_density * 100.).round(),
+        model.soup_symmetry
+    ));
+}
+
+/// The rotation or flip a key does to the selection or a floating paste.
+///
+/// Q/E turn a quarter counter clockwise/clockwise, U a half turn, H flips left to right (or top
+/// to bottom with Shift) and T flips along the diagonal (the other one with Shift).
+pub fn key_transform(app: &App, key: Key) -> Transform {
+    let shift = app.keys.mods.shift();
+
+    match key {
+        Key::Q => Transform::Rotate90,
+        Key::E => Transform::Rotate270,
+        Key::U => Transform::Rotate180,
+        Key::H if shift => Transform::FlipY,
+        Key::H => Transform::FlipX,
+        Key::T if shift => Transform::AntiTranspose,
+        Key::T => Transform::Transpose,
+        _ => Transform::Identity,
+    }
+}
+
 /// The function that is called when a key is pressed.
 ///
 /// See game_logic::model::model, where it is used.
@@ -14,8 +43,49 @@ pub fn key_pressed(app: &App, model: &mut Model, key: Key) {
         model.selection = None;
     }
 
+    // The analysis window closes on any key.
+    if model.analysis.take().is_some() {
+        return;
+    }
+
+    if model.recovery.is_some() {
+        recovery_key_pressed(app, model, key);
+        return;
+    }
+
+    if model.save_as.is_some() {
+        save_as_key_pressed(app, model, key);
+        return;
+    }
+
+    if model.clip_name.is_some() {
+        clip_name_key_pressed(app, model, key);
+        return;
+    }
+
+    if model.browser.is_some() {
+        browser_key_pressed(app, model, key);
+        return;
+    }
+
+    if model.library.is_some() {
+        library_key_pressed(app, model, key);
+        return;
+    }
+
+    if paste_key_pressed(app, model, key) {
+        return;
+    }
+
     if app.keys.mods.ctrl() {
         match key {
+            Key::C if app.keys.mods.shift() => {
+                // Name the selection if there is one, otherwise what's already in the clipboard.
+                if let Some(selection) = model.selection.take() {
+                    selection.copy(model);
+                }
+                open_clip_name(app, model);
+            }
             Key::C => {
                 if let Some(selection) = model.selection.take() {
                     selection.copy(model);
@@ -29,18 +99,70 @@ pub fn key_pressed(app: &App, model: &mut Model, key: Key) {
                     clear(model);
                 }
             }
+            Key::S if app.keys.mods.shift() => open_save_as(app, model),
             Key::S => {
-                save_board(model.board.clone());
-                notify_info("Board saved to file.");
+                // Save over the savestate the board came from, or make a new one.
+                let result = match model.savestate_name.clone() {
+                    Some(name) => write_savestate(model, &name, true).map(|()| name),
+                    None => save_board(model),
+                };
+                match result {
+                    Ok(name) => {
+                        notify_info(format!("Board saved to '{}'.", name));
+                        model.savestate_name = Some(name);
+                    }
+                    Err(e) => notify_error(e),
+                }
                 clear(model)
             }
-            Key::V => {
-                if model.clipboard.is_some() {
-                    Selection::paste(model);
+            Key::V if model.clipboard.is_some() => start_paste(app, model),
+            Key::L => {
+                open_library(app, model);
+                clear(model);
+            }
+            Key::E => {
+                if app.keys.mods.shift() {
+                    match &model.clipboard {
+                        Some(clipboard) => export_pattern(clipboard.clone()),
+                        None => notify_error("The clipboard is empty."),
+                    }
+                } else {
+                    export_pattern(model.board.tiles.clone());
                 }
             }
+            Key::P => {
+                let tiles = if app.keys.mods.shift() {
+                    model.board.tiles.clone()
+                } else if let Some(selection) = &model.selection {
+                    selection.get_inner_tiles(model)
+                } else {
+                    visible_tiles(model)
+                };
+                export_image(&tiles, model.grid_lines);
+            }
+            Key::G => match model.recorder {
+                Some(_) => stop_recording(model),
+                None => start_recording(model),
+            },
+            Key::W => select_clusters(model),
+            Key::F => {
+                if let Some(selection) = model.selection.take() {
+                    selection.fill(model);
+                }
+            }
+            Key::N => {
+                if let Some(selection) = model.selection.take() {
+                    selection.evolve(model, CONFIG.evolve_generations);
+                }
+            }
+            Key::R => {
+                model.soup_symmetry = model.soup_symmetry.next();
+                notify(format!("Soup symmetry: {}.", model.soup_symmetry));
+            }
             _ => (),
         }
+    } else if let Some(n) = clip_number(key) {
+        pick_clip(model, n);
     } else {
         match key {
             Key::Delete => {
@@ -68,14 +190,9 @@ pub fn key_pressed(app: &App, model: &mut Model, key: Key) {
                     selection.translate(model, 1, 0);
                 }
             }
-            Key::Q => {
+            Key::Q | Key::E | Key::H | Key::T | Key::U => {
                 if let Some(selection) = model.selection.take() {
-                    selection.rotate(model, Rotation::CW);
-                }
-            }
-            Key::E => {
-                if let Some(selection) = model.selection.take() {
-                    selection.rotate(model, Rotation::CCW);
+                    selection.transform(model, key_transform(app, key));
                 }
             }
             Key::Space => {
@@ -84,6 +201,7 @@ pub fn key_pressed(app: &App, model: &mut Model, key: Key) {
             }
             Key::C => {
                 model.board.clear();
+                model.generation = 0;
                 clear(model);
             }
             Key::G => model.grid_lines = !model.grid_lines,
@@ -92,10 +210,69 @@ pub fn key_pressed(app: &App, model: &mut Model, key: Key) {
                 clear(model);
             }
             Key::F => model.show_info = !model.show_info,
+            Key::N if app.keys.mods.shift() && model.selection.is_some() => {
+                if let Some(selection) = model.selection.take() {
+                    selection.evolve(model, 1);
+                }
+            }
+            Key::X => {
+                if let Some(selection) = model.selection.take() {
+                    selection.invert(model);
+                }
+            }
+            Key::Z => {
+                if let Some(selection) = model.selection.take() {
+                    selection.crop_to(model);
+                }
+            }
             Key::N => {
-                model.board.advance();
+                advance(model);
+                clear(model);
+            }
+            Key::P => {
+                if let Some(selection) = model.selection.take() {
+                    selection.find_predecessor(model);
+                }
+            }
+            Key::R => {
+                if let Some(selection) = model.selection.take() {
+                    selection.randomize(model);
+                } else {
+                    let seed = model.board.randomize(
+                        CONFIG.soup_density,
+                        model.soup_symmetry,
+                        CONFIG.soup_seed,
+                    );
+                    notify_soup(model, seed);
+                }
+            }
+            Key::I => {
+                if let Some(selection) = model.selection.take() {
+                    selection.analyze(model);
+                }
+            }
+            Key::O => {
+                if let Some(selection) = model.selection.take() {
+                    selection.search_periodic(model);
+                }
+            }
+            Key::V => {
+                if let Some(selection) = model.selection.take() {
+                    selection.export_svg(model);
+                }
+            }
+            Key::Back if !model.jobs.is_empty() => {
+                cancel_jobs(model);
+                notify("Cancelling running searches.");
+            }
+            Key::L => {
+                open_browser(app, model);
                 clear(model);
             }
+            Key::M => {
+                model.selection_tool = model.selection_tool.next();
+                notify(format!("Selection tool: {}.", model.selection_tool));
+            }
             Key::K => {
                 model.show_keybinds = !model.show_keybinds;
                 clear(model);
@@ -3,11 +3,16 @@
 //! If you're looking around, start in model.rs.
 
 pub mod cache;
+pub mod clipboard;
+pub mod files;
+pub mod jobs;
 pub mod keybinds;
 pub mod model;
 pub mod mouse;
 pub mod other_events;
+pub mod paste;
 pub mod selection;
+pub mod selection_tools;
 pub mod update;
 pub mod utils;
 pub mod view;
@@ -2,11 +2,15 @@
 //!
 //! Adds all the events and initializes the game state.
 
+use crate::cli::Args;
 use crate::{prelude::*, ui::notify_info};
 use clap::Parser;
 use fps_ticker::Fps;
 use grid::Grid;
 use nannou::text::Font;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
 
 /// Struct which stores all the game state.
 #[derive(Clone)]
@@ -23,11 +27,44 @@ pub struct Model {
     pub show_info: bool,
     pub fps: Fps,
     pub font: Font,
-    pub rulestring: String,
     pub selection: Option<Selection>,
+    /// The tool Ctrl+drag selects with.
+    pub selection_tool: SelectionTool,
+    /// The selection being dragged out with the mouse, if there is one.
+    pub selection_drag: Option<SelectionDrag>,
     pub keybinds: String,
     pub show_keybinds: bool,
     pub clipboard: Option<Grid<bool>>,
+    /// Recent clipboards, picked with the number keys.
+    pub clipboard_history: ClipboardHistory,
+    /// The name being typed for the clip in the clipboard, the prompt is open when it's Some.
+    pub clip_name: Option<String>,
+    /// The clipboard while it's floating under the mouse, waiting to be pasted.
+    pub paste: Option<Grid<bool>>,
+    /// How pastes are combined with the board, starts as CONFIG.paste_mode.
+    pub paste_mode: PasteMode,
+    /// How many times the board has advanced since it was cleared or loaded.
+    pub generation: u64,
+    pub jobs: Vec<Job>,
+    /// The report from the selection analyzer, shown in a window when it's Some.
+    pub analysis: Option<String>,
+    /// The savestate the board was last loaded from or saved to, which Ctrl+S saves over.
+    pub savestate_name: Option<String>,
+    /// The name being typed into the Save As prompt, open when it's Some.
+    pub save_as: Option<String>,
+    /// The autosave offered back after a crash, the prompt is open when it's Some.
+    pub recovery: Option<String>,
+    pub last_autosave: Instant,
+    /// The session lock, None if another instance of the game already has it.
+    pub session: Option<Arc<Session>>,
+    /// The savestate browser, open when it's Some.
+    pub browser: Option<SavestateBrowser>,
+    /// The pattern library picker, open when it's Some.
+    pub library: Option<PatternPicker>,
+    /// The recording being made, started and stopped with Ctrl+G.
+    pub recorder: Option<Recorder>,
+    /// Symmetry used when randomizing, starts as CONFIG.soup_symmetry.
+    pub soup_symmetry: SoupSymmetry,
 }
 
 impl Model {
@@ -37,20 +74,6 @@ impl Model {
     }
 }
 
-/// Struct which holds the arguments that can be passed to the program.
-#[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
-struct Args {
-    /// Used to load a savestate with a given name when launching the program.
-    #[arg(short, long)]
-    load: Option<String>,
-
-    /// Print out a savestate with a given name instead of launching the program, used for
-    /// debugging.
-    #[arg(short, long)]
-    print: Option<String>,
-}
-
 /// Entry point function.
 ///
 /// Sets everything up.
@@ -62,12 +85,14 @@ pub fn model(app: &App) -> Model {
         .key_pressed(key_pressed)
         .mouse_moved(mouse_moved)
         .mouse_wheel(mouse_wheel)
+        .dropped_file(dropped_file)
+        .received_character(received_character)
         .build()
         .unwrap();
 
     let initial_tile_size = CONFIG.tile_size;
 
-    let (mut board, width, height) = if CONFIG.autosize_board {
+    let (board, width, height) = if CONFIG.autosize_board {
         let rect = app.window_rect();
         let width = (rect.w() / CONFIG.tile_size).ceil() as usize;
         let height = (rect.h() / CONFIG.tile_size).ceil() as usize;
@@ -84,24 +109,10 @@ pub fn model(app: &App) -> Model {
     };
 
     let args = Args::parse();
-    let mut paused = false;
-
-    if args.load.is_some() {
-        board = load_savestate(args.load.unwrap());
-        board.set_wh(width, height);
-        paused = true;
-        notify_info("Savestate loaded.");
-    }
-
-    if args.print.is_some() {
-        let board = load_savestate(args.print.unwrap());
-        board.print();
-        app.quit();
-    }
 
     let mut model = Model {
         board,
-        paused,
+        paused: false,
         pressed: None,
         last_mouse_pos: (0., 0.),
         cache: Cache::new((width, height), initial_tile_size),
@@ -112,18 +123,64 @@ pub fn model(app: &App) -> Model {
         show_info: false,
         fps: Fps::default(),
         font: load_font(),
-        rulestring: CONFIG.rule.serialize(),
         selection: None,
+        selection_tool: SelectionTool::default(),
+        selection_drag: None,
         // This include_str! is a macro meaning that it runs at compile time, so once you've
         // compiled the program, this macro is replaced with whatever text is inside keybinds.txt
         // when you compiled, so it doesn't need the file to be run.
         keybinds: include_str!("../../assets/keybinds.txt").to_string(),
         show_keybinds: false,
         clipboard: None,
+        clipboard_history: ClipboardHistory::default(),
+        clip_name: None,
+        paste: None,
+        paste_mode: CONFIG.paste_mode,
+        generation: 0,
+        jobs: Vec::new(),
+        analysis: None,
+        savestate_name: None,
+        save_as: None,
+        recovery: None,
+        last_autosave: Instant::now(),
+        session: None,
+        browser: None,
+        library: None,
+        recorder: None,
+        soup_symmetry: CONFIG.soup_symmetry,
     };
 
     model.cache.update((width, height), CONFIG.tile_size);
 
+    if let Some(name) = args.load {
+        // Pattern files are loaded by path, savestates by name.
+        if Path::new(&name).is_file() {
+            match read_pattern(&name) {
+                Ok(file) => {
+                    apply_file_rule(&file);
+                    load_into_board(&mut model, &file.tiles);
+                    notify_info("Pattern loaded.");
+                }
+                Err(e) => notify_error(e),
+            }
+        } else {
+            match open_savestate(&mut model, name) {
+                Ok(()) => notify_info("Savestate loaded."),
+                Err(e) => notify_error(e),
+            }
+        }
+        model.paused = true;
+    }
+
+    model.session = start_session(&session_lock_path()).map(Arc::new);
+    if model
+        .session
+        .as_ref()
+        .is_some_and(|session| session.crashed)
+    {
+        offer_recovery(app, &mut model);
+    }
+
     notify_info("Press K to show keybinds");
 
     model
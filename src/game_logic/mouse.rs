@@ -20,10 +20,8 @@ pub fn mouse_wheel(_app: &App, model: &mut Model, delta: MouseScrollDelta, _phas
 pub fn mouse_moved(app: &App, model: &mut Model, pos: Vec2) {
     model.mouse_pos = (pos.x, pos.y);
 
-    if let Some(selection) = &mut model.selection {
-        if app.keys.mods.ctrl() && app.mouse.buttons.left().is_down() {
-            selection.end = pixel_to_board(pos, &model.cache).into();
-        }
+    if app.mouse.buttons.left().is_down() {
+        drag_selection(model, pos);
     }
 
     if model.last_mouse_pressed == Some(MouseButton::Middle) {
@@ -46,18 +44,30 @@ pub fn mouse_moved(app: &App, model: &mut Model, pos: Vec2) {
 
 /// Handles placing/removing tiles, and starting selections.
 pub fn mouse_pressed(app: &App, model: &mut Model, button: MouseButton) {
+    // Clicking places a floating paste, right clicking drops it.
+    if model.paste.is_some() {
+        match button {
+            MouseButton::Left => return commit_paste(app, model),
+            MouseButton::Right => return cancel_paste(app, model),
+            _ => (),
+        }
+    }
+
     model.pressed = Some(button);
 
     if app.keys.mods.ctrl() {
         if let MouseButton::Left = button {
-            let (x, y) = pixel_to_board(app.mouse.position(), &model.cache);
-            model.selection = Some(Selection::new(x, y));
+            start_selection(app, model);
         }
     } else {
         model.selection = None;
     }
 }
 
-pub fn mouse_released(_app: &App, model: &mut Model, _button: MouseButton) {
+pub fn mouse_released(_app: &App, model: &mut Model, button: MouseButton) {
     model.pressed = None;
+
+    if button == MouseButton::Left {
+        finish_selection(model);
+    }
 }
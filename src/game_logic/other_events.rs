@@ -2,6 +2,32 @@
 
 use crate::prelude::*;
 
+/// Dropping a pattern file or an image onto the window loads it into the clipboard.
+pub fn dropped_file(_app: &App, model: &mut Model, path: std::path::PathBuf) {
+    if is_image(&path) {
+        load_image_to_clipboard(model, path);
+    } else {
+        load_pattern_to_clipboard(model, path);
+    }
+}
+
+/// Typing goes to the Save As prompt or the savestate browser's search box when they're open.
+pub fn received_character(_app: &App, model: &mut Model, c: char) {
+    if let Some(name) = &mut model.save_as {
+        if !c.is_control() {
+            name.push(c);
+        }
+    } else if let Some(name) = &mut model.clip_name {
+        if !c.is_control() {
+            name.push(c);
+        }
+    } else if let Some(browser) = &mut model.browser {
+        browser.type_char(c);
+    } else if let Some(library) = &mut model.library {
+        library.type_char(c);
+    }
+}
+
 /// Doesn't do much if CONFIG.autosize_board is false (default).
 pub fn window_resized(_app: &App, model: &mut Model, rect: Vec2) {
     if CONFIG.autosize_board {
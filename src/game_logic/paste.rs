@@ -0,0 +1,171 @@
+//! Floating pastes, which follow the mouse as a preview until they're placed.
+//!
+//! Ctrl+V picks up the clipboard. While it's floating, Q/E/U/H/T rotate and flip it (like the
+//! selection), Tab changes how it's combined with the cells under it, clicking places it, and right
+//! click or Escape cancels. Any other key works as normal.
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How a paste is combined with the cells already on the board.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PasteMode {
+    /// Replaces everything in the pasted area, including with dead cells.
+    #[default]
+    Copy,
+    /// Adds the live cells, leaving everything else alone.
+    Or,
+    /// Only keeps cells that are alive in both.
+    And,
+    /// Flips the cells under the live cells of the paste.
+    Xor,
+}
+
+impl PasteMode {
+    pub fn combine(self, board: bool, paste: bool) -> bool {
+        match self {
+            PasteMode::Copy => paste,
+            PasteMode::Or => board | paste,
+            PasteMode::And => board & paste,
+            PasteMode::Xor => board ^ paste,
+        }
+    }
+    /// The next mode, for cycling through them with a key.
+    pub fn next(self) -> Self {
+        match self {
+            PasteMode::Copy => PasteMode::Or,
+            PasteMode::Or => PasteMode::And,
+            PasteMode::And => PasteMode::Xor,
+            PasteMode::Xor => PasteMode::Copy,
+        }
+    }
+}
+
+impl fmt::Display for PasteMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_uppercase())
+    }
+}
+
+/// Combines some tiles into the board with their bottom left corner at (x, y). Anything off the
+/// board is left out.
+pub fn paste_into(board: &mut Board, tiles: &Grid<bool>, (x, y): (isize, isize), mode: PasteMode) {
+    for ((dy, dx), tile) in tiles.indexed_iter() {
+        let (bx, by) = (x + dx as isize, y + dy as isize);
+        if bx >= 0 && by >= 0 {
+            let old = board.get_or_empty(bx, by);
+            board.set(bx as usize, by as usize, mode.combine(old, *tile));
+        }
+    }
+}
+
+/// Picks up the clipboard, to be placed with the mouse.
+pub fn start_paste(app: &App, model: &mut Model) {
+    if let Some(clipboard) = &model.clipboard {
+        app.set_exit_on_escape(false);
+        model.paste = Some(clipboard.clone());
+        model.selection = None;
+        notify(format!(
+            "Click to paste ({}). Q/E/U: Rotate. H/T: Flip. Tab: Change mode. Esc: Cancel.",
+            model.paste_mode
+        ));
+    }
+}
+
+pub fn cancel_paste(app: &App, model: &mut Model) {
+    app.set_exit_on_escape(true);
+    model.paste = None;
+}
+
+/// Where the bottom left of the floating paste is, so it's centered on the mouse.
+fn paste_position(model: &Model, tiles: &Grid<bool>) -> (isize, isize) {
+    let (x, y) = pixel_to_board(f32_to_vec2(model.mouse_pos), &model.cache);
+
+    (
+        x as isize - tiles.cols() as isize / 2,
+        y as isize - tiles.rows() as isize / 2,
+    )
+}
+
+/// Places the floating paste under the mouse, and selects it.
+pub fn commit_paste(app: &App, model: &mut Model) {
+    let Some(tiles) = model.paste.take() else {
+        return;
+    };
+    app.set_exit_on_escape(true);
+
+    let (x, y) = paste_position(model, &tiles);
+    paste_into(&mut model.board, &tiles, (x, y), model.paste_mode);
+
+    // Select the part of the paste that's on the board.
+    let (w, h) = model.board.wh();
+    let (end_x, end_y) = (x + tiles.cols() as isize - 1, y + tiles.rows() as isize - 1);
+    if end_x >= 0 && end_y >= 0 && x < w as isize && y < h as isize {
+        model.selection = Some(Selection::rect(
+            VecU2::new(x.max(0) as usize, y.max(0) as usize),
+            VecU2::new((end_x as usize).min(w - 1), (end_y as usize).min(h - 1)),
+        ));
+    }
+}
+
+/// Handles a key press while a paste is floating, returning whether the key was used.
+pub fn paste_key_pressed(app: &App, model: &mut Model, key: Key) -> bool {
+    let Some(tiles) = &mut model.paste else {
+        return false;
+    };
+
+    let transform = match key {
+        Key::Q | Key::E | Key::H | Key::T | Key::U if !app.keys.mods.ctrl() => {
+            key_transform(app, key)
+        }
+        Key::Tab => {
+            model.paste_mode = model.paste_mode.next();
+            notify(format!("Paste mode: {}.", model.paste_mode));
+            return true;
+        }
+        Key::Return => {
+            commit_paste(app, model);
+            return true;
+        }
+        Key::Escape => {
+            cancel_paste(app, model);
+            return true;
+        }
+        _ => return false,
+    };
+    *tiles = transform.apply_grid(tiles);
+
+    true
+}
+
+/// Draws the floating paste as see-through cells under the mouse.
+pub fn draw_paste(draw: &Draw, model: &Model) {
+    let Some(tiles) = &model.paste else {
+        return;
+    };
+    let cache = &model.cache;
+    let (x, y) = paste_position(model, tiles);
+    let (w, h) = model.board.wh();
+
+    let color = CONFIG.cell_color.to_srgb();
+    for ((dy, dx), tile) in tiles.indexed_iter() {
+        let (bx, by) = (x + dx as isize, y + dy as isize);
+        if *tile && bx >= 0 && by >= 0 && (bx as usize) < w && (by as usize) < h {
+            let (px, py) = board_xy_to_pixel((bx as usize, by as usize), cache);
+            draw.rect()
+                .x_y(px, py)
+                .w_h(cache.tile_size, cache.tile_size)
+                .color(srgba(color.red, color.green, color.blue, 0.5));
+        }
+    }
+
+    let corner = |x: isize, y: isize| VecU2::new(x.max(0) as usize, y.max(0) as usize);
+    outline(
+        draw,
+        cache,
+        corner(x, y),
+        corner(x + tiles.cols() as isize - 1, y + tiles.rows() as isize - 1),
+    );
+}
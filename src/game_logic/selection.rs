@@ -1,32 +1,117 @@
 //! Handles everything to do with the selection.
+//!
+//! A selection is a rectangle, with an optional mask saying which cells inside it are selected, so
+//! it can be any shape (see selection_tools.rs for the lasso and magic wand). Everything here only
+//! touches the masked cells.
 
 use crate::prelude::*;
-use nalgebra::{Matrix2, Vector2};
+use crate::search::periodic::{PeriodicSearch, SearchResult};
+use crate::search::predecessor::{find_predecessor, PredecessorResult};
+
+/// A line between two cell corners, relative to the bottom left corner of the selection.
+pub type Edge = [(usize, usize); 2];
 
 /// Struct that stores the current selection.
 #[derive(Clone)]
 pub struct Selection {
     pub start: VecU2,
     pub end: VecU2,
-}
-
-/// What type of rotation to do on the selection.
-#[allow(clippy::upper_case_acronyms)]
-#[derive(PartialEq, Eq)]
-pub enum Rotation {
-    CW,
-    CCW,
+    /// Which cells of the rectangle from start to end are selected, bottom row first. None means
+    /// all of them.
+    mask: Option<Grid<bool>>,
+    /// The outline of the mask, worked out once so drawing it is cheap.
+    edges: Vec<Edge>,
 }
 
 impl Selection {
     pub fn new(x: usize, y: usize) -> Self {
+        Self::rect(VecU2::new(x, y), VecU2::new(x, y))
+    }
+    /// Selects every cell in a rectangle.
+    pub fn rect(start: VecU2, end: VecU2) -> Self {
         Self {
-            start: VecU2::new(x, y),
-            end: VecU2::new(x, y),
+            start,
+            end,
+            mask: None,
+            edges: Vec::new(),
         }
     }
+    /// Selects the cells of a rectangle that are set in the mask, which has to be the same size.
+    fn masked(start: VecU2, end: VecU2, mask: Option<Grid<bool>>) -> Self {
+        let mask = mask.filter(|mask| !mask.iter().all(|m| *m));
+
+        Self {
+            start,
+            end,
+            edges: mask.as_ref().map(mask_edges).unwrap_or_default(),
+            mask,
+        }
+    }
+    /// Selects some cells. Returns None if there aren't any.
+    pub fn from_cells(cells: impl IntoIterator<Item = (usize, usize)>) -> Option<Self> {
+        let cells: Vec<(usize, usize)> = cells.into_iter().collect();
+        let min_x = cells.iter().map(|(x, _)| *x).min()?;
+        let min_y = cells.iter().map(|(_, y)| *y).min()?;
+        let max_x = cells.iter().map(|(x, _)| *x).max()?;
+        let max_y = cells.iter().map(|(_, y)| *y).max()?;
+
+        let mut mask = Grid::new(max_y - min_y + 1, max_x - min_x + 1);
+        for (x, y) in &cells {
+            mask[(y - min_y, x - min_x)] = true;
+        }
+
+        Some(Self::masked(
+            VecU2::new(min_x, min_y),
+            VecU2::new(max_x, max_y),
+            Some(mask),
+        ))
+    }
+    /// Selects the cells from `min` to `max` (inclusive) that `selected` is true for, shrunk down
+    /// to fit them. Returns None if there aren't any.
+    pub fn from_fn(
+        (min_x, min_y): (usize, usize),
+        (max_x, max_y): (usize, usize),
+        selected: impl Fn(usize, usize) -> bool,
+    ) -> Option<Self> {
+        let rows = (min_y..=max_y).filter(|y| (min_x..=max_x).any(|x| selected(x, *y)));
+        let cols = (min_x..=max_x).filter(|x| (min_y..=max_y).any(|y| selected(*x, y)));
+        let (min_y, max_y) = (rows.clone().next()?, rows.last()?);
+        let (min_x, max_x) = (cols.clone().next()?, cols.last()?);
+
+        let mut mask = Grid::new(max_y - min_y + 1, max_x - min_x + 1);
+        for ((y, x), cell) in mask.indexed_iter_mut() {
+            *cell = selected(min_x + x, min_y + y);
+        }
+
+        Some(Self::masked(
+            VecU2::new(min_x, min_y),
+            VecU2::new(max_x, max_y),
+            Some(mask),
+        ))
+    }
+    /// The outline of the selection if it's masked (rectangles don't need one).
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
     pub fn render(&self, draw: &Draw, cache: &Cache) {
-        outline(draw, cache, self.start, self.end);
+        if self.mask.is_none() {
+            return outline(draw, cache, self.start, self.end);
+        }
+
+        let (min_x, min_y) = self.min();
+        let half = 0.5 * cache.tile_size;
+        let corner = |(x, y): (usize, usize)| {
+            let (px, py) = board_xy_to_pixel((min_x + x, min_y + y), cache);
+            Vec2::new(px - half, py - half)
+        };
+
+        for [start, end] in &self.edges {
+            draw.line()
+                .color(CONFIG.selection_color.to_srgb())
+                .weight(CONFIG.selection_thickness)
+                .start(corner(*start))
+                .end(corner(*end));
+        }
     }
     pub fn width(&self) -> usize {
         self.start.x.abs_diff(self.end.x)
@@ -37,160 +122,293 @@ impl Selection {
     pub fn wh(&self) -> (usize, usize) {
         (self.width(), self.height())
     }
-    /// Returns a grid of the selected tiles.
-    pub fn get_inner_tiles(&self, model: &Model) -> Grid<bool> {
+    /// The bottom left corner of the selection's rectangle.
+    pub fn min(&self) -> (usize, usize) {
+        (self.start.x.min(self.end.x), self.start.y.min(self.end.y))
+    }
+    /// Whether a cell on the board is selected.
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        let (min_x, min_y) = self.min();
         let (w, h) = self.wh();
-        let (w, h) = (w + 1, h + 1);
-        let min_x = self.start.x.min(self.end.x);
-        let min_y = self.start.y.min(self.end.y);
-        let board = &model.board;
+        if x < min_x || y < min_y || x > min_x + w || y > min_y + h {
+            return false;
+        }
 
-        let mut grid = Grid::new(h, w);
+        match &self.mask {
+            Some(mask) => mask[(y - min_y, x - min_x)],
+            None => true,
+        }
+    }
+    /// The selected cells on the board.
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        let (min_x, min_y) = self.min();
+        let (w, h) = self.wh();
 
-        for dx in 0..w {
-            for dy in 0..h {
-                let (x, y) = (min_x + dx, min_y + dy);
+        (min_y..=min_y + h)
+            .flat_map(|y| (min_x..=min_x + w).map(move |x| (x, y)))
+            .filter(|(x, y)| self.contains(*x, *y))
+            .collect()
+    }
+    /// Returns a grid of the selected tiles.
+    pub fn get_inner_tiles(&self, model: &Model) -> Grid<bool> {
+        self.board_tiles(&model.board)
+    }
+    /// Returns a grid of the selected tiles on a board. Cells outside the mask are dead.
+    pub fn board_tiles(&self, board: &Board) -> Grid<bool> {
+        let (w, h) = self.wh();
+        let (min_x, min_y) = self.min();
 
-                if let Some(tile) = grid.get_mut(dy, dx) {
-                    *tile = board.get_or_empty(x as isize, y as isize);
-                } else {
-                    panic!("Attempted to access out of bounds tile at ({}, {})", dx, dy);
-                }
-            }
+        let mut grid = Grid::new(h + 1, w + 1);
+        for ((dy, dx), tile) in grid.indexed_iter_mut() {
+            let (x, y) = (min_x + dx, min_y + dy);
+            *tile = self.contains(x, y) && board.get_or_empty(x as isize, y as isize);
         }
 
         grid
     }
     pub fn copy(&self, model: &mut Model) {
-        model.clipboard = Some(self.get_inner_tiles(model));
-    }
-    /// Pastes the clipboard into the board.
-    pub fn paste(model: &mut Model) {
-        if let Some(clipboard) = &model.clipboard {
-            let (x, y) = pixel_to_board(model.mouse_pos.into(), &model.cache);
-            let (w, h) = (clipboard.cols(), clipboard.rows());
-            model.board.set_area(VecU2::new(x, y), clipboard);
-            model.selection = Some(Selection {
-                start: VecU2::new(x, y),
-                end: VecU2::new(x + w - 1, y + h - 1),
-            });
-        }
+        set_clipboard(model, self.get_inner_tiles(model));
     }
     /// Clears the selection.
     pub fn clear(&self, model: &mut Model) {
-        let (w, h) = self.wh();
-        let (w, h) = (w + 1, h + 1);
-        let min_x = self.start.x.min(self.end.x);
-        let min_y = self.start.y.min(self.end.y);
-
-        for dx in 0..w {
-            for dy in 0..h {
-                let (x, y) = (min_x + dx, min_y + dy);
-
-                model.board.set(x, y, false);
+        self.clear_board(&mut model.board);
+    }
+    /// Clears the selected cells on a board.
+    pub fn clear_board(&self, board: &mut Board) {
+        for (x, y) in self.cells() {
+            board.set(x, y, false);
+        }
+    }
+    /// Rotates or flips the selected cells, and selects where they end up.
+    pub fn transform(&self, model: &mut Model, transform: Transform) {
+        match self.transform_board(&mut model.board, transform) {
+            Ok(selection) => model.selection = Some(selection),
+            Err(e) => {
+                notify_error(e);
+                model.selection = Some(self.clone());
             }
         }
     }
-    /// Rotates the selection by 90 degrees either way.
+    /// Rotates or flips the selected cells on a board, returning the new selection.
     ///
-    /// Uses some super sick LINEAR ALGEBRA!!!
-    pub fn rotate(&self, model: &mut Model, rotation: Rotation) {
+    /// The result is centered where the selection was, rounding the same way both ways so that
+    /// turning back and forth doesn't drift. It's then pushed back inside the board if it would go
+    /// over an edge. If it's too big for the board, the board is left alone.
+    pub fn transform_board(
+        &self,
+        board: &mut Board,
+        transform: Transform,
+    ) -> Result<Selection, String> {
         let (w, h) = self.wh();
-        let min_x = self.start.x.min(self.end.x);
-        let min_y = self.start.y.min(self.end.y);
+        let (w, h) = (w + 1, h + 1);
+        let (min_x, min_y) = self.min();
 
-        let center_x = min_x as isize + w as isize / 2;
-        let center_y = min_y as isize + h as isize / 2;
+        let transformed = transform.apply_grid(&self.board_tiles(board));
+        let mask = self.mask.as_ref().map(|mask| transform.apply_grid(mask));
+        let (new_w, new_h) = (transformed.cols(), transformed.rows());
+        if new_w > board.width() || new_h > board.height() {
+            return Err("The selection doesn't fit on the board that way round.".to_string());
+        }
 
-        let matrix = match rotation {
-            Rotation::CW => Matrix2::new(0, -1, 1, 0),
-            Rotation::CCW => Matrix2::new(0, 1, -1, 0),
+        let fit = |min: usize, old: usize, new: usize, board: usize| -> usize {
+            let centered = min as isize + (old as isize - new as isize) / 2;
+            centered.clamp(0, (board - new) as isize) as usize
         };
+        let new_x = fit(min_x, w, new_w, board.width());
+        let new_y = fit(min_y, h, new_h, board.height());
+
+        self.clear_board(board);
+        paste_into(
+            board,
+            &transformed,
+            (new_x as isize, new_y as isize),
+            PasteMode::Or,
+        );
+
+        Ok(Selection::masked(
+            VecU2::new(new_x, new_y),
+            VecU2::new(new_x + new_w - 1, new_y + new_h - 1),
+            mask,
+        ))
+    }
+    /// Fills the selection with random cells.
+    pub fn randomize(&self, model: &mut Model) {
+        let (w, h) = self.wh();
+        let (min_x, min_y) = self.min();
+        let seed = CONFIG.soup_seed.unwrap_or_else(rand::random);
 
-        let original_grid = self.get_inner_tiles(model);
-
-        for dx in 0..=w {
-            for dy in 0..=h {
-                model.board.set(min_x + dx, min_y + dy, false);
-            }
+        let soup = random_soup(w + 1, h + 1, CONFIG.soup_density, model.soup_symmetry, seed);
+        for (x, y) in self.cells() {
+            model.board.set(x, y, soup[(y - min_y, x - min_x)]);
         }
 
-        let mut new_min_x = isize::MAX;
-        let mut new_max_x = isize::MIN;
-        let mut new_min_y = isize::MAX;
-        let mut new_max_y = isize::MIN;
-
-        for dx in 0..=w {
-            for dy in 0..=h {
-                if let Some(&tile) = original_grid.get(dy, dx) {
-                    if !tile {
-                        continue;
-                    }
-
-                    let (x, y) = (min_x + dx, min_y + dy);
-
-                    let vector = Vector2::new(x as isize - center_x, y as isize - center_y);
-
-                    let transformed_vector = matrix * vector;
-
-                    let new_x = (transformed_vector.x + center_x) as usize;
-                    let new_y = (transformed_vector.y + center_y) as usize;
+        notify_soup(model, seed);
+        model.selection = Some(self.clone());
+    }
+    /// Flips every selected cell between alive and dead.
+    pub fn invert(&self, model: &mut Model) {
+        self.invert_board(&mut model.board);
+        model.selection = Some(self.clone());
+    }
+    pub fn invert_board(&self, board: &mut Board) {
+        for (x, y) in self.cells() {
+            let alive = board.get_or_empty(x as isize, y as isize);
+            board.set(x, y, !alive);
+        }
+    }
+    /// Fills the selection with copies of the clipboard, tiled from the bottom left corner.
+    pub fn fill(&self, model: &mut Model) {
+        let Some(clipboard) = &model.clipboard else {
+            notify_error("The clipboard is empty.");
+            model.selection = Some(self.clone());
+            return;
+        };
+        self.fill_board(&mut model.board, &clipboard.clone());
+        model.selection = Some(self.clone());
+    }
+    pub fn fill_board(&self, board: &mut Board, tiles: &Grid<bool>) {
+        let (rows, cols) = (tiles.rows(), tiles.cols());
+        if rows == 0 || cols == 0 {
+            return;
+        }
 
-                    model.board.set(new_x, new_y, tile);
+        let (min_x, min_y) = self.min();
+        for (x, y) in self.cells() {
+            board.set(x, y, tiles[((y - min_y) % rows, (x - min_x) % cols)]);
+        }
+    }
+    /// Advances the selected cells on their own, as if nothing outside the selection existed.
+    /// Cells that would be born outside it are dropped.
+    pub fn evolve(&self, model: &mut Model, generations: usize) {
+        self.evolve_board(&mut model.board, generations, &current_rule());
+        model.selection = Some(self.clone());
+    }
+    /// Advances the selected cells on a board, on their own.
+    pub fn evolve_board(&self, board: &mut Board, generations: usize, rule: &Rule) {
+        let (min_x, min_y) = self.min();
+        let inside = |x: i32, y: i32| {
+            x >= 0 && y >= 0 && self.contains(min_x + x as usize, min_y + y as usize)
+        };
 
-                    new_min_x = new_min_x.min(new_x as isize);
-                    new_max_x = new_max_x.max(new_x as isize);
-                    new_min_y = new_min_y.min(new_y as isize);
-                    new_max_y = new_max_y.max(new_y as isize);
-                }
-            }
+        let mut pattern = Pattern::from_grid(&self.board_tiles(board));
+        for _ in 0..generations {
+            let next = pattern.step(rule);
+            pattern = Pattern::new(next.cells().iter().copied().filter(|(x, y)| inside(*x, *y)));
         }
 
-        let new_selection = Selection {
-            start: VecU2::new(new_min_x as usize, new_min_y as usize),
-            end: VecU2::new(new_max_x as usize, new_max_y as usize),
+        self.clear_board(board);
+        for &(x, y) in pattern.cells() {
+            board.set(min_x + x as usize, min_y + y as usize, true);
+        }
+    }
+    /// Shrinks the board down to the selection. Anything outside the mask is cleared.
+    pub fn crop_to(&self, model: &mut Model) {
+        let board = Board {
+            tiles: self.get_inner_tiles(model),
         };
-
-        model.selection = Some(new_selection);
+        replace_board(model, board);
+        model.cache.target_camera_offset = (0., 0.);
+
+        notify(format!(
+            "Board cropped to {}x{}.",
+            model.board.width(),
+            model.board.height()
+        ));
     }
-    /// Moves the selection around the board.
-    pub fn translate(&self, model: &mut Model, dx: isize, dy: isize) {
-        let (w, h) = self.wh();
-        let min_x = self.start.x.min(self.end.x);
-        let min_y = self.start.y.min(self.end.y);
-
-        let original_grid = self.get_inner_tiles(model);
+    /// Exports the selection as an SVG, labelled with its place on the board.
+    pub fn export_svg(&self, model: &mut Model) {
+        export_svg(&self.get_inner_tiles(model), self.min(), model.grid_lines);
+        model.selection = Some(self.clone());
+    }
+    /// Runs the selected cells on their own, and shows what kind of object they are.
+    pub fn analyze(&self, model: &mut Model) {
+        let pattern = Pattern::from_grid(&self.get_inner_tiles(model));
+        let analysis = analyze(&pattern, &current_rule(), MAX_ANALYSIS_GENERATIONS);
 
-        for dx in 0..=w {
-            for dy in 0..=h {
-                model.board.set(min_x + dx, min_y + dy, false);
+        model.analysis = Some(format!("Selection analysis:\n\n{}", analysis));
+        model.selection = Some(self.clone());
+    }
+    /// Starts a background search for a pattern that evolves into the selection.
+    ///
+    /// The result goes into the clipboard, ready to be pasted one cell up and left of the
+    /// selection.
+    pub fn find_predecessor(&self, model: &mut Model) {
+        let target = self.get_inner_tiles(model);
+        let rule = current_rule();
+
+        let job = Job::spawn("predecessor search", move |cancel| {
+            match find_predecessor(&target, &rule, cancel) {
+                PredecessorResult::Found(parent) => JobOutput::Clipboard(
+                    parent,
+                    "Predecessor found, and copied to the clipboard.".to_string(),
+                ),
+                PredecessorResult::GardenOfEden => {
+                    JobOutput::Info("No predecessor exists (Garden of Eden).".to_string())
+                }
+                PredecessorResult::Cancelled => {
+                    JobOutput::Info("Predecessor search cancelled.".to_string())
+                }
             }
-        }
+        });
 
-        for dx_offset in 0..=w {
-            for dy_offset in 0..=h {
-                if let Some(&tile) = original_grid.get(dy_offset, dx_offset) {
-                    if tile {
-                        let new_x = (min_x as isize + dx_offset as isize + dx) as usize;
-                        let new_y = (min_y as isize + dy_offset as isize + dy) as usize;
-                        model.board.set(new_x, new_y, true);
-                    }
+        start_job(model, job);
+        model.selection = Some(self.clone());
+    }
+    /// Starts a background search for an oscillator or spaceship that fits inside the selection,
+    /// using the period and displacement from the config.
+    pub fn search_periodic(&self, model: &mut Model) {
+        let (w, h) = self.wh();
+        let [dx, dy] = CONFIG.search_displacement;
+        let rule = current_rule();
+        let mut search = PeriodicSearch::new(&rule, CONFIG.search_period, dx, dy, w + 1, h + 1);
+
+        let job = Job::spawn("oscillator/spaceship search", move |cancel| {
+            match search.next(&rule, cancel, |_| ()) {
+                SearchResult::Found(pattern) => JobOutput::Clipboard(
+                    pattern.to_grid(),
+                    "Found a pattern, and copied it to the clipboard.".to_string(),
+                ),
+                SearchResult::Exhausted => {
+                    JobOutput::Info("Nothing with that period fits in the selection.".to_string())
                 }
+                SearchResult::Cancelled => JobOutput::Info("Search cancelled.".to_string()),
             }
-        }
+        });
 
-        let new_selection = Selection {
-            start: VecU2::new(
-                (self.start.x as isize + dx) as usize,
-                (self.start.y as isize + dy) as usize,
-            ),
-            end: VecU2::new(
-                (self.end.x as isize + dx) as usize,
-                (self.end.y as isize + dy) as usize,
-            ),
+        start_job(model, job);
+        model.selection = Some(self.clone());
+    }
+    /// Moves the selection around the board.
+    pub fn translate(&self, model: &mut Model, dx: isize, dy: isize) {
+        let (w, h) = model.board.wh();
+        let moved = |v: usize, d: isize, size: usize| v.checked_add_signed(d).filter(|v| *v < size);
+
+        // Don't let the selection go off the board.
+        let (Some(start_x), Some(end_x), Some(start_y), Some(end_y)) = (
+            moved(self.start.x, dx, w),
+            moved(self.end.x, dx, w),
+            moved(self.start.y, dy, h),
+            moved(self.end.y, dy, h),
+        ) else {
+            model.selection = Some(self.clone());
+            return;
         };
 
-        model.selection = Some(new_selection);
+        let tiles = self.get_inner_tiles(model);
+        self.clear(model);
+        let (min_x, min_y) = (start_x.min(end_x), start_y.min(end_y));
+        paste_into(
+            &mut model.board,
+            &tiles,
+            (min_x as isize, min_y as isize),
+            PasteMode::Or,
+        );
+
+        model.selection = Some(Selection {
+            start: VecU2::new(start_x, start_y),
+            end: VecU2::new(end_x, end_y),
+            ..self.clone()
+        });
     }
 }
 
@@ -222,3 +440,43 @@ pub fn outline(draw: &Draw, cache: &Cache, start: VecU2, end: VecU2) {
             .end(Vec2::new(ex, ey));
     }
 }
+
+/// The lines between selected and unselected cells in a mask, with lines along the same row or
+/// column joined up.
+fn mask_edges(mask: &Grid<bool>) -> Vec<Edge> {
+    let (rows, cols) = (mask.rows(), mask.cols());
+    let at = |x: usize, y: usize| mask.get(y, x).copied().unwrap_or(false);
+    // Whether there's an edge below (x, y), and to the left of it.
+    let below = |x: usize, y: usize| x < cols && at(x, y) != (y > 0 && at(x, y - 1));
+    let left = |x: usize, y: usize| y < rows && at(x, y) != (x > 0 && at(x - 1, y));
+
+    let mut edges = Vec::new();
+    for y in 0..=rows {
+        let mut from = None;
+        for x in 0..=cols {
+            match (below(x, y), from) {
+                (true, None) => from = Some(x),
+                (false, Some(start)) => {
+                    edges.push([(start, y), (x, y)]);
+                    from = None;
+                }
+                _ => (),
+            }
+        }
+    }
+    for x in 0..=cols {
+        let mut from = None;
+        for y in 0..=rows {
+            match (left(x, y), from) {
+                (true, None) => from = Some(y),
+                (false, Some(start)) => {
+                    edges.push([(x, start), (x, y)]);
+                    from = None;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    edges
+}
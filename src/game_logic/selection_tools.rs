@@ -0,0 +1,331 @@
+//! The ways of making a selection with the mouse: rectangles, lassos and the magic wand.
+//!
+//! Ctrl+drag uses the current tool (M changes it). Holding Shift as well adds to the selection,
+//! and Alt takes away from it. The magic wand selects the cluster of live cells that was clicked,
+//! where cells count as touching if they're within CONFIG.wand_gap cells of each other, using
+//! CONFIG.wand_adjacency.
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelectionTool {
+    #[default]
+    Rectangle,
+    /// Selects everything inside a shape drawn with the mouse.
+    Lasso,
+    /// Selects a cluster of live cells.
+    Wand,
+}
+
+impl SelectionTool {
+    /// The next tool, for cycling through them with a key.
+    pub fn next(self) -> Self {
+        match self {
+            SelectionTool::Rectangle => SelectionTool::Lasso,
+            SelectionTool::Lasso => SelectionTool::Wand,
+            SelectionTool::Wand => SelectionTool::Rectangle,
+        }
+    }
+}
+
+impl fmt::Display for SelectionTool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SelectionTool::Rectangle => "rectangle",
+            SelectionTool::Lasso => "lasso",
+            SelectionTool::Wand => "magic wand",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Which cells count as touching, for the magic wand.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Adjacency {
+    /// The eight cells around a cell, including diagonals.
+    #[default]
+    Moore,
+    /// Only the four cells sharing an edge.
+    VonNeumann,
+}
+
+impl Adjacency {
+    /// Whether two cells `dx` and `dy` apart are within `distance` of each other.
+    fn within(self, dx: usize, dy: usize, distance: usize) -> bool {
+        match self {
+            Adjacency::Moore => dx.max(dy) <= distance,
+            Adjacency::VonNeumann => dx + dy <= distance,
+        }
+    }
+}
+
+/// How a new selection is combined with the one that's already there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionOp {
+    Replace,
+    Add,
+    Subtract,
+}
+
+impl SelectionOp {
+    /// Shift adds, Alt subtracts.
+    pub fn from_mods(app: &App) -> Self {
+        if app.keys.mods.shift() {
+            SelectionOp::Add
+        } else if app.keys.mods.alt() {
+            SelectionOp::Subtract
+        } else {
+            SelectionOp::Replace
+        }
+    }
+    pub fn combine(self, base: Option<&Selection>, new: Option<Selection>) -> Option<Selection> {
+        let (Some(base), SelectionOp::Add | SelectionOp::Subtract) = (base, self) else {
+            return new;
+        };
+        let Some(new) = new else {
+            return Some(base.clone());
+        };
+
+        let ((base_x, base_y), (base_w, base_h)) = (base.min(), base.wh());
+        let max = (base_x + base_w, base_y + base_h);
+        match self {
+            SelectionOp::Add => {
+                let ((new_x, new_y), (new_w, new_h)) = (new.min(), new.wh());
+                Selection::from_fn(
+                    (base_x.min(new_x), base_y.min(new_y)),
+                    (max.0.max(new_x + new_w), max.1.max(new_y + new_h)),
+                    |x, y| base.contains(x, y) || new.contains(x, y),
+                )
+            }
+            _ => Selection::from_fn((base_x, base_y), max, |x, y| {
+                base.contains(x, y) && !new.contains(x, y)
+            }),
+        }
+    }
+}
+
+/// A selection that's being dragged out with the mouse.
+#[derive(Clone)]
+pub struct SelectionDrag {
+    /// The selection from before, which the new one is added to or taken away from.
+    pub base: Option<Selection>,
+    pub op: SelectionOp,
+    pub tool: SelectionTool,
+    /// The cells the mouse has been over. Rectangles only use the first and last.
+    pub points: Vec<(usize, usize)>,
+}
+
+impl SelectionDrag {
+    /// The selection the drag makes, before it's combined with the old one.
+    fn shape(&self) -> Option<Selection> {
+        let (first, last) = (*self.points.first()?, *self.points.last()?);
+
+        match self.tool {
+            SelectionTool::Lasso => lasso(&self.points),
+            _ => Some(Selection::rect(first.into(), last.into())),
+        }
+    }
+    pub fn render(&self, draw: &Draw, cache: &Cache) {
+        if let Some(base) = &self.base {
+            base.render(draw, cache);
+        }
+
+        match self.tool {
+            SelectionTool::Lasso => {
+                let points = self
+                    .points
+                    .iter()
+                    .chain(self.points.first())
+                    .map(|point| f32_to_vec2(board_xy_to_pixel(*point, cache)));
+                draw.polyline()
+                    .weight(CONFIG.selection_thickness)
+                    .color(CONFIG.selection_color.to_srgb())
+                    .points(points);
+            }
+            _ => {
+                if let Some(shape) = self.shape() {
+                    shape.render(draw, cache);
+                }
+            }
+        }
+    }
+}
+
+/// Selects the cells inside a loop, and the ones along it.
+pub fn lasso(points: &[(usize, usize)]) -> Option<Selection> {
+    let min_x = points.iter().map(|(x, _)| *x).min()?;
+    let min_y = points.iter().map(|(_, y)| *y).min()?;
+    let max_x = points.iter().map(|(x, _)| *x).max()?;
+    let max_y = points.iter().map(|(_, y)| *y).max()?;
+
+    let mut mask = Grid::new(max_y - min_y + 1, max_x - min_x + 1);
+    for (x, y) in points {
+        mask[(y - min_y, x - min_x)] = true;
+    }
+
+    // Even-odd rule, a row at a time: a cell is inside if a line going right from it crosses the
+    // loop an odd number of times, so the cells from the first crossing to the second are inside,
+    // then from the third to the fourth, and so on.
+    let corners = corners(points);
+    let mut crossings = Vec::new();
+    for y in min_y..=max_y {
+        let row = y as f32;
+        crossings.clear();
+        for (i, &(ax, ay)) in corners.iter().enumerate() {
+            let (bx, by) = corners[(i + 1) % corners.len()];
+            let (ax, ay, bx, by) = (ax as f32, ay as f32, bx as f32, by as f32);
+            if (ay > row) != (by > row) {
+                crossings.push(ax + (row - ay) / (by - ay) * (bx - ax));
+            }
+        }
+        crossings.sort_by(f32::total_cmp);
+
+        for span in crossings.chunks_exact(2) {
+            let start = (span[0].ceil() as usize).max(min_x);
+            let end = (span[1].ceil() as usize).min(max_x + 1);
+            for x in start..end {
+                mask[(y - min_y, x - min_x)] = true;
+            }
+        }
+    }
+
+    Selection::from_fn((min_x, min_y), (max_x, max_y), |x, y| {
+        mask[(y - min_y, x - min_x)]
+    })
+}
+
+/// The points where a loop turns. Points in the middle of a straight line don't change its shape,
+/// and dragging slowly leaves a lot of them.
+fn corners(points: &[(usize, usize)]) -> Vec<(i64, i64)> {
+    let mut corners: Vec<(i64, i64)> = Vec::new();
+
+    for &(x, y) in points {
+        let point = (x as i64, y as i64);
+        if let [.., a, b] = corners[..] {
+            if (b.0 - a.0) * (point.1 - b.1) == (b.1 - a.1) * (point.0 - b.0) {
+                corners.pop();
+            }
+        }
+        if corners.last() != Some(&point) {
+            corners.push(point);
+        }
+    }
+
+    corners
+}
+
+/// Finds the clusters of live cells that the seeds are part of.
+pub fn clusters(
+    board: &Board,
+    seeds: impl IntoIterator<Item = (usize, usize)>,
+    adjacency: Adjacency,
+    gap: usize,
+) -> Vec<(usize, usize)> {
+    let alive = |x: usize, y: usize| board.get_or_empty(x as isize, y as isize);
+    let distance = gap + 1;
+
+    let mut seen = HashSet::new();
+    let mut queue: VecDeque<(usize, usize)> = seeds
+        .into_iter()
+        .filter(|(x, y)| alive(*x, *y))
+        .filter(|cell| seen.insert(*cell))
+        .collect();
+    let mut cells = Vec::new();
+
+    while let Some((x, y)) = queue.pop_front() {
+        cells.push((x, y));
+
+        for ny in y.saturating_sub(distance)..=(y + distance).min(board.height().saturating_sub(1))
+        {
+            for nx in
+                x.saturating_sub(distance)..=(x + distance).min(board.width().saturating_sub(1))
+            {
+                if adjacency.within(x.abs_diff(nx), y.abs_diff(ny), distance)
+                    && alive(nx, ny)
+                    && seen.insert((nx, ny))
+                {
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+    }
+
+    cells
+}
+
+/// Selects the cluster of live cells at (x, y), if there's a live cell there.
+pub fn magic_wand(board: &Board, x: usize, y: usize) -> Option<Selection> {
+    Selection::from_cells(clusters(
+        board,
+        [(x, y)],
+        CONFIG.wand_adjacency,
+        CONFIG.wand_gap,
+    ))
+}
+
+/// Grows the selection to the whole of every cluster of live cells it touches.
+pub fn select_clusters(model: &mut Model) {
+    let Some(selection) = model.selection.take() else {
+        return;
+    };
+
+    let cells = clusters(
+        &model.board,
+        selection.cells(),
+        CONFIG.wand_adjacency,
+        CONFIG.wand_gap,
+    );
+    match Selection::from_cells(cells) {
+        Some(clusters) => model.selection = Some(clusters),
+        None => {
+            notify_error("There are no live cells in the selection.");
+            model.selection = Some(selection);
+        }
+    }
+}
+
+/// Starts a selection where the mouse was pressed, with the current tool.
+pub fn start_selection(app: &App, model: &mut Model) {
+    let (x, y) = pixel_to_board(app.mouse.position(), &model.cache);
+    let op = SelectionOp::from_mods(app);
+    let base = model
+        .selection
+        .take()
+        .filter(|_| op != SelectionOp::Replace);
+
+    match model.selection_tool {
+        SelectionTool::Wand => {
+            model.selection = op.combine(base.as_ref(), magic_wand(&model.board, x, y));
+        }
+        tool => {
+            model.selection_drag = Some(SelectionDrag {
+                base,
+                op,
+                tool,
+                points: vec![(x, y)],
+            });
+        }
+    }
+}
+
+/// Adds the cell under the mouse to the selection being dragged out.
+pub fn drag_selection(model: &mut Model, pos: Vec2) {
+    let point = pixel_to_board(pos, &model.cache);
+
+    if let Some(drag) = &mut model.selection_drag {
+        if drag.points.last() != Some(&point) {
+            drag.points.push(point);
+        }
+    }
+}
+
+/// Finishes the selection being dragged out.
+pub fn finish_selection(model: &mut Model) {
+    if let Some(drag) = model.selection_drag.take() {
+        model.selection = drag.op.combine(drag.base.as_ref(), drag.shape());
+    }
+}
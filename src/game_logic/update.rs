@@ -35,14 +35,18 @@ pub fn update(app: &App, model: &mut Model, _update: Update) {
             + model.cache.target_tile_size * smoothing_factor;
     }
 
+    poll_jobs(model);
+    autosave_if_due(model);
+
     // Only run the logic when not paused.
     if !model.paused {
-        time!("advance", { model.board.advance() });
+        time!("advance", { advance(model) });
     }
 
     // Drawing && selections.
     if let Some(button) = model.pressed {
-        if model.selection.is_none() {
+        // Ctrl is for selecting, not drawing.
+        if model.selection.is_none() && model.selection_drag.is_none() && !app.keys.mods.ctrl() {
             let mut set = |to: bool| {
                 let pos = app.mouse.position();
                 let (x, y) = pixel_to_board(pos, &model.cache);
@@ -100,3 +104,16 @@ pub fn update(app: &App, model: &mut Model, _update: Update) {
         model.last_mouse_pressed = None;
     }
 }
+
+/// Advances the board one generation, adding it to the recording if there is one.
+pub fn advance(model: &mut Model) {
+    model.board.advance();
+    model.generation += 1;
+
+    if let Some(recorder) = &mut model.recorder {
+        if !recorder.capture(&model.board.tiles) {
+            notify(format!("Recordings stop at {} frames.", MAX_FRAMES));
+            stop_recording(model);
+        }
+    }
+}
@@ -83,3 +83,22 @@ pub fn print_grid(grid: Grid<bool>) {
         println!();
     }
 }
+
+/// The part of the board that's on screen.
+pub fn visible_tiles(model: &Model) -> Grid<bool> {
+    let cache = &model.cache;
+    let (w, h) = (cache.window_size.0 / 2., cache.window_size.1 / 2.);
+    let (min_x, min_y) = pixel_to_board(Vec2::new(-w, -h), cache);
+    let (max_x, max_y) = pixel_to_board(Vec2::new(w, h), cache);
+    let (max_x, max_y) = (
+        max_x.min(model.board.width()),
+        max_y.min(model.board.height()),
+    );
+
+    let mut tiles = Grid::new(max_y.saturating_sub(min_y), max_x.saturating_sub(min_x));
+    for ((y, x), tile) in tiles.indexed_iter_mut() {
+        *tile = model.board.get(min_x + x, min_y + y).unwrap_or(false);
+    }
+
+    tiles
+}
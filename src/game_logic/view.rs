@@ -32,6 +32,10 @@ pub fn view(app: &App, model: &Model, frame: Frame) {
         if let Some(selection) = &model.selection {
             selection.render(&draw, cache);
         }
+        if let Some(drag) = &model.selection_drag {
+            drag.render(&draw, cache);
+        }
+        draw_paste(&draw, model);
 
         time!("notifications", {
             draw_notifications(app, &draw, model);
@@ -45,6 +49,25 @@ pub fn view(app: &App, model: &Model, frame: Frame) {
             .build()
             .render(&draw, cache, model);
 
+        if let Some(analysis) = &model.analysis {
+            UiWindow::new()
+                .text(analysis.trim_end())
+                .open(true)
+                .build()
+                .render(&draw, cache, model);
+        }
+
+        if let Some(browser) = &model.browser {
+            browser.render(&draw, cache, model);
+        }
+        if let Some(library) = &model.library {
+            library.render(&draw, cache, model);
+        }
+
+        draw_save_as(&draw, cache, model);
+        draw_clip_name(&draw, cache, model);
+        draw_recovery(&draw, cache, model);
+
         clear_timers();
 
         draw.to_frame(app, &frame).unwrap();
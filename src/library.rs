@@ -0,0 +1,224 @@
+//! The pattern library: classic patterns that come with the game, plus any the user adds.
+//!
+//! The bundled patterns are RLE files in /assets/patterns, one folder per category, embedded at
+//! compile time like keybinds.txt. The user's own patterns go in BASE_DIR/library (or any folder
+//! in CONFIG.library_dirs), in any format `read_pattern` can read. Patterns in a subfolder use it
+//! as their category. Named clips (see game_logic::clipboard) are listed too.
+
+use crate::prelude::*;
+use std::fs;
+use std::path::Path;
+
+/// The bundled patterns, with the folder (category) they're in.
+const BUNDLED: &[(&str, &str)] = &[
+    (
+        "still_lifes",
+        include_str!("../assets/patterns/still_lifes/block.rle"),
+    ),
+    (
+        "still_lifes",
+        include_str!("../assets/patterns/still_lifes/beehive.rle"),
+    ),
+    (
+        "still_lifes",
+        include_str!("../assets/patterns/still_lifes/loaf.rle"),
+    ),
+    (
+        "still_lifes",
+        include_str!("../assets/patterns/still_lifes/boat.rle"),
+    ),
+    (
+        "still_lifes",
+        include_str!("../assets/patterns/still_lifes/tub.rle"),
+    ),
+    (
+        "oscillators",
+        include_str!("../assets/patterns/oscillators/blinker.rle"),
+    ),
+    (
+        "oscillators",
+        include_str!("../assets/patterns/oscillators/toad.rle"),
+    ),
+    (
+        "oscillators",
+        include_str!("../assets/patterns/oscillators/beacon.rle"),
+    ),
+    (
+        "oscillators",
+        include_str!("../assets/patterns/oscillators/pulsar.rle"),
+    ),
+    (
+        "oscillators",
+        include_str!("../assets/patterns/oscillators/pentadecathlon.rle"),
+    ),
+    (
+        "spaceships",
+        include_str!("../assets/patterns/spaceships/glider.rle"),
+    ),
+    (
+        "spaceships",
+        include_str!("../assets/patterns/spaceships/lwss.rle"),
+    ),
+    (
+        "spaceships",
+        include_str!("../assets/patterns/spaceships/mwss.rle"),
+    ),
+    (
+        "spaceships",
+        include_str!("../assets/patterns/spaceships/hwss.rle"),
+    ),
+    (
+        "guns",
+        include_str!("../assets/patterns/guns/gosper_glider_gun.rle"),
+    ),
+    (
+        "guns",
+        include_str!("../assets/patterns/guns/simkin_glider_gun.rle"),
+    ),
+    (
+        "puffers",
+        include_str!("../assets/patterns/puffers/puffer_2.rle"),
+    ),
+    (
+        "methuselahs",
+        include_str!("../assets/patterns/methuselahs/r_pentomino.rle"),
+    ),
+    (
+        "methuselahs",
+        include_str!("../assets/patterns/methuselahs/diehard.rle"),
+    ),
+    (
+        "methuselahs",
+        include_str!("../assets/patterns/methuselahs/acorn.rle"),
+    ),
+    (
+        "methuselahs",
+        include_str!("../assets/patterns/methuselahs/pi_heptomino.rle"),
+    ),
+];
+
+/// The category for user patterns that aren't in a subfolder.
+const USER_CATEGORY: &str = "Custom";
+/// The category for named clips.
+const CLIP_CATEGORY: &str = "Clips";
+
+/// A pattern in the library.
+#[derive(Clone, Debug)]
+pub struct LibraryPattern {
+    pub name: String,
+    pub category: String,
+    pub file: PatternFile,
+    pub thumbnail: Grid<bool>,
+}
+
+impl LibraryPattern {
+    fn new(
+        file: PatternFile,
+        fallback_name: &str,
+        category: String,
+        thumbnail_size: usize,
+    ) -> Self {
+        Self {
+            name: file
+                .name
+                .clone()
+                .unwrap_or_else(|| fallback_name.to_string()),
+            category,
+            thumbnail: thumbnail(&file.tiles, thumbnail_size),
+            file,
+        }
+    }
+    /// The first comment in the file, which the bundled patterns use for a short description.
+    pub fn description(&self) -> &str {
+        self.file.comments.first().map_or("", String::as_str)
+    }
+}
+
+/// The bundled patterns, followed by the user's, sorted by category then name.
+pub fn load_library(thumbnail_size: usize) -> Vec<LibraryPattern> {
+    let mut patterns = bundled_patterns(thumbnail_size);
+
+    let mut user_patterns = Vec::new();
+    read_library_dir(
+        Path::new(&clip_dir()),
+        CLIP_CATEGORY,
+        thumbnail_size,
+        &mut user_patterns,
+    );
+    for dir in std::iter::once(library_dir()).chain(CONFIG.library_dirs.iter().cloned()) {
+        read_library_dir(
+            Path::new(&dir),
+            USER_CATEGORY,
+            thumbnail_size,
+            &mut user_patterns,
+        );
+    }
+    user_patterns.sort_by(|a, b| (&a.category, &a.name).cmp(&(&b.category, &b.name)));
+    patterns.extend(user_patterns);
+
+    patterns
+}
+
+/// The patterns that come with the game.
+pub fn bundled_patterns(thumbnail_size: usize) -> Vec<LibraryPattern> {
+    BUNDLED
+        .iter()
+        .map(|(folder, text)| {
+            let file = parse_rle(text).expect("bundled patterns are valid RLE");
+            LibraryPattern::new(file, "Unnamed", category_name(folder), thumbnail_size)
+        })
+        .collect()
+}
+
+/// Reads the patterns in a folder, and (one level down) in its subfolders.
+fn read_library_dir(
+    dir: &Path,
+    category: &str,
+    thumbnail_size: usize,
+    patterns: &mut Vec<LibraryPattern>,
+) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        if path.is_dir() {
+            if category == USER_CATEGORY {
+                read_library_dir(&path, &name, thumbnail_size, patterns);
+            }
+        } else if Format::from_extension(&path).is_some()
+            || path.extension().is_some_and(|e| e == "lif")
+        {
+            match read_pattern(&path) {
+                Ok(file) => patterns.push(LibraryPattern::new(
+                    file,
+                    &name,
+                    category.to_string(),
+                    thumbnail_size,
+                )),
+                Err(e) => notify_error(e),
+            }
+        }
+    }
+}
+
+/// Turns a folder name like still_lifes into Still lifes.
+fn category_name(folder: &str) -> String {
+    let name = folder.replace('_', " ");
+    let mut chars = name.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+/// The folder the user's own patterns go in.
+pub fn library_dir() -> String {
+    BASE_DIR.to_string() + "/library"
+}
@@ -0,0 +1,260 @@
+//! Works out what kind of object a pattern is, by running it on its own until it repeats.
+
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+
+/// How long to run a pattern before deciding it never stabilizes.
+pub const MAX_ANALYSIS_GENERATIONS: usize = 2000;
+
+/// What a pattern turned out to be.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Analysis {
+    DiesOut {
+        generations: usize,
+    },
+    StillLife {
+        settled_after: usize,
+        population: usize,
+    },
+    Oscillator {
+        settled_after: usize,
+        period: usize,
+        min_population: usize,
+        max_population: usize,
+        rotor: usize,
+        stator: usize,
+        /// Average number of cells that change each generation.
+        heat: f32,
+        /// How much of the oscillator moves, rotor / (rotor + stator).
+        volatility: f32,
+    },
+    Spaceship {
+        settled_after: usize,
+        period: usize,
+        dx: i32,
+        dy: i32,
+        min_population: usize,
+        max_population: usize,
+    },
+    Unstable {
+        generations: usize,
+    },
+}
+
+/// Runs a pattern in isolation until it repeats itself (possibly moved), and describes what it
+/// ended up as.
+pub fn analyze(pattern: &Pattern, rule: &Rule, max_generations: usize) -> Analysis {
+    let mut history: Vec<Pattern> = vec![pattern.clone()];
+    let mut seen: HashMap<Pattern, usize> = HashMap::new();
+    let mut current = pattern.clone();
+
+    for generation in 0..=max_generations {
+        if current.is_empty() {
+            return Analysis::DiesOut {
+                generations: generation,
+            };
+        }
+
+        if let Some(&start) = seen.get(&current.normalized()) {
+            return describe(&history[start..generation], start, &current);
+        }
+
+        seen.insert(current.normalized(), generation);
+        current = current.step(rule);
+        history.push(current.clone());
+    }
+
+    Analysis::Unstable {
+        generations: max_generations,
+    }
+}
+
+/// Describes one full period of a pattern. `next` is the generation after the last one in `cycle`.
+fn describe(cycle: &[Pattern], settled_after: usize, next: &Pattern) -> Analysis {
+    let period = cycle.len();
+    let first = &cycle[0];
+    let populations = cycle.iter().map(Pattern::population);
+    let min_population = populations.clone().min().unwrap_or(0);
+    let max_population = populations.max().unwrap_or(0);
+
+    let (Some((start, _)), Some((end, _))) = (first.bounds(), next.bounds()) else {
+        return Analysis::DiesOut {
+            generations: settled_after,
+        };
+    };
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+
+    if dx != 0 || dy != 0 {
+        return Analysis::Spaceship {
+            settled_after,
+            period,
+            dx,
+            dy,
+            min_population,
+            max_population,
+        };
+    }
+
+    if period == 1 {
+        return Analysis::StillLife {
+            settled_after,
+            population: first.population(),
+        };
+    }
+
+    let mut times_alive: HashMap<(i32, i32), usize> = HashMap::new();
+    for generation in cycle {
+        for cell in generation.cells() {
+            *times_alive.entry(*cell).or_insert(0) += 1;
+        }
+    }
+    let stator = times_alive.values().filter(|&&n| n == period).count();
+    let rotor = times_alive.len() - stator;
+
+    let changes: usize = (0..period)
+        .map(|t| {
+            let (a, b) = (&cycle[t], &cycle[(t + 1) % period]);
+            let born = b
+                .cells()
+                .iter()
+                .filter(|(x, y)| !a.contains(*x, *y))
+                .count();
+            let died = a
+                .cells()
+                .iter()
+                .filter(|(x, y)| !b.contains(*x, *y))
+                .count();
+            born + died
+        })
+        .sum();
+
+    Analysis::Oscillator {
+        settled_after,
+        period,
+        min_population,
+        max_population,
+        rotor,
+        stator,
+        heat: changes as f32 / period as f32,
+        volatility: rotor as f32 / (rotor + stator) as f32,
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Writes a speed the usual way, like c/4 for the glider or 2c/5 for some other ship.
+fn speed(dx: i32, dy: i32, period: usize) -> String {
+    let (ax, ay) = (dx.unsigned_abs() as usize, dy.unsigned_abs() as usize);
+
+    if ax != 0 && ay != 0 && ax != ay {
+        return format!("({}, {})c/{}", ax, ay, period);
+    }
+
+    let distance = ax.max(ay);
+    let divisor = gcd(distance, period);
+    let (distance, period) = (distance / divisor, period / divisor);
+
+    match distance {
+        1 => format!("c/{}", period),
+        _ => format!("{}c/{}", distance, period),
+    }
+}
+
+/// Which way a ship is going, where y goes up the screen.
+fn direction(dx: i32, dy: i32) -> String {
+    let vertical = match dy.signum() {
+        1 => "up",
+        -1 => "down",
+        _ => "",
+    };
+    let horizontal = match dx.signum() {
+        1 => "right",
+        -1 => "left",
+        _ => "",
+    };
+
+    let kind = if dx == 0 || dy == 0 {
+        "orthogonal"
+    } else if dx.abs() == dy.abs() {
+        "diagonal"
+    } else {
+        "oblique"
+    };
+
+    match (vertical, horizontal) {
+        (way, "") | ("", way) => format!("{} {}", kind, way),
+        (vertical, horizontal) => format!("{} {} and {}", kind, vertical, horizontal),
+    }
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let settled = |f: &mut fmt::Formatter<'_>, settled_after: usize| {
+            if settled_after > 0 {
+                writeln!(f, "settles after {} generations", settled_after)
+            } else {
+                Ok(())
+            }
+        };
+
+        match self {
+            Analysis::DiesOut { generations } => {
+                writeln!(f, "Dies out")?;
+                writeln!(f, "after {} generations", generations)
+            }
+            Analysis::StillLife {
+                settled_after,
+                population,
+            } => {
+                writeln!(f, "Still life")?;
+                settled(f, *settled_after)?;
+                writeln!(f, "population: {}", population)
+            }
+            Analysis::Oscillator {
+                settled_after,
+                period,
+                min_population,
+                max_population,
+                rotor,
+                stator,
+                heat,
+                volatility,
+            } => {
+                writeln!(f, "Oscillator")?;
+                settled(f, *settled_after)?;
+                writeln!(f, "period: {}", period)?;
+                writeln!(f, "population: {}-{}", min_population, max_population)?;
+                writeln!(f, "rotor: {} cells", rotor)?;
+                writeln!(f, "stator: {} cells", stator)?;
+                writeln!(f, "heat: {:.2}", heat)?;
+                writeln!(f, "volatility: {:.2}", volatility)
+            }
+            Analysis::Spaceship {
+                settled_after,
+                period,
+                dx,
+                dy,
+                min_population,
+                max_population,
+            } => {
+                writeln!(f, "Spaceship")?;
+                settled(f, *settled_after)?;
+                writeln!(f, "period: {}", period)?;
+                writeln!(f, "speed: {}", speed(*dx, *dy, *period))?;
+                writeln!(f, "direction: {}", direction(*dx, *dy))?;
+                writeln!(f, "population: {}-{}", min_population, max_population)
+            }
+            Analysis::Unstable { generations } => {
+                writeln!(f, "Never stabilizes")?;
+                writeln!(f, "(gave up after {} generations)", generations)
+            }
+        }
+    }
+}
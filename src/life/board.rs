@@ -4,8 +4,6 @@ use crate::prelude::*;
 use rayon::prelude::*;
 use std::collections::HashSet;
 
-use crate::config::CONFIG;
-
 /// Struct for the board.
 #[derive(Clone, Debug)]
 pub struct Board {
@@ -30,7 +28,7 @@ impl Board {
     pub fn advance(&mut self) {
         let width = self.width();
         let height = self.height();
-        let rule = &CONFIG.rule;
+        let rule = current_rule();
 
         let mut next_tiles = vec![false; width * height];
 
@@ -204,6 +202,13 @@ impl Board {
             self.set(*x, *y, to);
         });
     }
+    /// Fills the whole board with random cells, returning the seed used so it can be repeated.
+    pub fn randomize(&mut self, density: f64, symmetry: SoupSymmetry, seed: Option<u64>) -> u64 {
+        let seed = seed.unwrap_or_else(rand::random);
+        self.tiles = random_soup(self.width(), self.height(), density, symmetry, seed);
+
+        seed
+    }
     pub fn print(&self) {
         print_grid(self.tiles.clone());
     }
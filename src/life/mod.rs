@@ -1,7 +1,15 @@
 //! Handles the board and life logic.
 
+pub use analysis::*;
 pub use board::*;
+pub use pattern::*;
 pub use rulestring::*;
+pub use soup::*;
+pub use transform::*;
 
+mod analysis;
 mod board;
+mod pattern;
 mod rulestring;
+mod soup;
+mod transform;
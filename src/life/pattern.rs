@@ -0,0 +1,132 @@
+//! A pattern is just a list of live cells, with no board around it.
+//!
+//! The board has edges, which is what you want when drawing, but not when searching for things or
+//! running something "in isolation". Patterns grow as much as they need to.
+
+use crate::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// A set of live cells, stored as a sorted list of coordinates.
+///
+/// Keeping the list sorted means two patterns with the same cells are always equal (and hash the
+/// same), which the searches rely on.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pattern {
+    cells: Vec<(i32, i32)>,
+}
+
+impl Pattern {
+    pub fn new(cells: impl IntoIterator<Item = (i32, i32)>) -> Self {
+        let mut cells: Vec<(i32, i32)> = cells.into_iter().collect();
+        cells.sort_unstable();
+        cells.dedup();
+
+        Self { cells }
+    }
+    /// Creates a pattern from a grid, where column is x and row is y.
+    pub fn from_grid(grid: &Grid<bool>) -> Self {
+        let cells = grid
+            .indexed_iter()
+            .filter(|(_, alive)| **alive)
+            .map(|((y, x), _)| (x as i32, y as i32));
+
+        Self::new(cells)
+    }
+    /// Turns the pattern into a grid that just fits it, with the top left cell at (0, 0).
+    pub fn to_grid(&self) -> Grid<bool> {
+        let Some(((min_x, min_y), (max_x, max_y))) = self.bounds() else {
+            return Grid::new(0, 0);
+        };
+
+        let mut grid = Grid::new((max_y - min_y + 1) as usize, (max_x - min_x + 1) as usize);
+        for (x, y) in &self.cells {
+            grid[((y - min_y) as usize, (x - min_x) as usize)] = true;
+        }
+
+        grid
+    }
+    pub fn cells(&self) -> &[(i32, i32)] {
+        &self.cells
+    }
+    pub fn population(&self) -> usize {
+        self.cells.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        self.cells.binary_search(&(x, y)).is_ok()
+    }
+    /// The smallest and largest coordinates of the live cells, or None if the pattern is empty.
+    pub fn bounds(&self) -> Option<((i32, i32), (i32, i32))> {
+        let first = *self.cells.first()?;
+
+        Some(self.cells.iter().fold(
+            (first, first),
+            |((min_x, min_y), (max_x, max_y)), &(x, y)| {
+                ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y)))
+            },
+        ))
+    }
+    pub fn translate(&self, dx: i32, dy: i32) -> Self {
+        Self {
+            cells: self.cells.iter().map(|(x, y)| (x + dx, y + dy)).collect(),
+        }
+    }
+    /// Moves the pattern so its bounding box starts at (0, 0).
+    pub fn normalized(&self) -> Self {
+        match self.bounds() {
+            Some(((min_x, min_y), _)) => self.translate(-min_x, -min_y),
+            None => self.clone(),
+        }
+    }
+    pub fn transform(&self, transform: Transform) -> Self {
+        Self::new(self.cells.iter().map(|&(x, y)| transform.apply(x, y)))
+    }
+    /// The same pattern no matter how it was rotated, flipped or moved. Two patterns are the same
+    /// object if their canonical forms are equal.
+    pub fn canonical(&self) -> Self {
+        Transform::ALL
+            .iter()
+            .map(|t| self.transform(*t).normalized())
+            .min()
+            .unwrap_or_default()
+    }
+    /// Advances the pattern by one generation.
+    ///
+    /// Rules where cells are born with 0 neighbors (B0) would fill the infinite plane, so cells
+    /// are only ever born next to existing ones.
+    pub fn step(&self, rule: &Rule) -> Self {
+        let mut counts: HashMap<(i32, i32), u8> = HashMap::with_capacity(self.cells.len() * 8);
+
+        for &(x, y) in &self.cells {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if dx != 0 || dy != 0 {
+                        *counts.entry((x + dx, y + dy)).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        let alive: HashSet<(i32, i32)> = self.cells.iter().copied().collect();
+
+        let survivors = self
+            .cells
+            .iter()
+            .filter(|cell| !counts.contains_key(cell) && rule.survive(0))
+            .copied();
+
+        let next = counts.iter().filter_map(|(cell, &count)| {
+            let lives = if alive.contains(cell) {
+                rule.survive(count)
+            } else {
+                rule.born(count)
+            };
+
+            lives.then_some(*cell)
+        });
+
+        Self::new(next.chain(survivors))
+    }
+}
@@ -1,8 +1,25 @@
 //! Rules for the game of life.
 
+use crate::config::CONFIG;
+use lazy_static::lazy_static;
 use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
+use std::sync::RwLock;
+
+lazy_static! {
+    /// The rule the game is running. Starts as CONFIG.rule, but pattern files can change it.
+    static ref RULE: RwLock<Rule> = RwLock::new(CONFIG.rule);
+}
+
+/// The rule the game is currently running.
+pub fn current_rule() -> Rule {
+    *RULE.read().unwrap()
+}
+
+pub fn set_rule(rule: Rule) {
+    *RULE.write().unwrap() = rule;
+}
 
 /// Holds the rules for the game as a u32.
 ///
@@ -13,39 +30,77 @@ use std::fmt;
 ///
 /// The next 9 bits are the same, but it't weather or not a new one is born with that number of
 /// neighbors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Rule(u32);
 
-/// Stupid shit.
-#[derive(Deserialize)]
-struct RuleHolder {
-    rule: Rule,
-}
-
 impl Rule {
     pub fn survive(&self, count: u8) -> bool {
         (self.0 >> count) & 1 == 1
     }
 
     pub fn born(&self, count: u8) -> bool {
-        (self.0 >> (count + 8)) & 1 == 1
+        (self.0 >> (count + 9)) & 1 == 1
+    }
+    fn counts(&self, f: impl Fn(&Self, u8) -> bool) -> String {
+        (0..=8)
+            .filter(|i| f(self, *i))
+            .map(|i| i.to_string())
+            .collect()
     }
     pub fn serialize(&self) -> String {
-        let mut survive_str = String::new();
-        let mut born_str = String::new();
+        format!("{}/{}", self.counts(Rule::survive), self.counts(Rule::born))
+    }
+    /// Writes the rule in B/S notation, like B3/S23. This is what pattern files use.
+    pub fn to_bs(&self) -> String {
+        format!(
+            "B{}/S{}",
+            self.counts(Rule::born),
+            self.counts(Rule::survive)
+        )
+    }
+    /// Reads a rule in either S/B notation (like 23/3) or B/S notation (like B3/S23).
+    pub fn parse(rulestring: &str) -> Result<Rule, String> {
+        let rulestring = rulestring.trim();
+        let parts: Vec<&str> = rulestring.split('/').collect();
+        if parts.len() != 2 {
+            return Err("Invalid rulestring format".to_string());
+        }
 
-        for i in 0..8 {
-            if self.survive(i) {
-                survive_str.push_str(&i.to_string());
+        let (survive_part, born_part) = if parts[0].starts_with(['B', 'b']) {
+            (parts[1], parts[0])
+        } else {
+            (parts[0], parts[1])
+        };
+        let survive_part = survive_part.trim_start_matches(['S', 's']);
+        let born_part = born_part.trim_start_matches(['B', 'b']);
+
+        let mut rule_value: u32 = 0;
+
+        for ch in survive_part.chars() {
+            if let Some(digit) = ch.to_digit(10) {
+                if digit <= 8 {
+                    rule_value |= 1 << digit;
+                } else {
+                    return Err("Survival count out of range".to_string());
+                }
+            } else {
+                return Err("Invalid character in survival string".to_string());
             }
         }
 
-        for i in 0..8 {
-            if self.born(i) {
-                born_str.push_str(&i.to_string());
+        for ch in born_part.chars() {
+            if let Some(digit) = ch.to_digit(10) {
+                if digit <= 8 {
+                    rule_value |= 1 << (digit + 9);
+                } else {
+                    return Err("Birth count out of range".to_string());
+                }
+            } else {
+                return Err("Invalid character in birth string".to_string());
             }
         }
 
-        format!("{}/{}", survive_str, born_str)
+        Ok(Rule(rule_value))
     }
 }
 
@@ -54,10 +109,7 @@ impl Rule {
 
 impl From<&str> for Rule {
     fn from(rulestring: &str) -> Self {
-        let rule_holder: RuleHolder =
-            toml::from_str(&format!("rule = \"{}\"", rulestring)).unwrap();
-
-        rule_holder.rule
+        Rule::parse(rulestring).unwrap()
     }
 }
 
@@ -88,41 +140,7 @@ impl<'de> Deserialize<'de> for Rule {
             where
                 E: de::Error,
             {
-                let parts: Vec<&str> = value.split('/').collect();
-                if parts.len() != 2 {
-                    return Err(de::Error::custom("Invalid rulestring format"));
-                }
-
-                let survive_part = parts[0];
-                let born_part = parts[1];
-
-                let mut rule_value: u32 = 0;
-
-                for ch in survive_part.chars() {
-                    if let Some(digit) = ch.to_digit(10) {
-                        if digit < 8 {
-                            rule_value |= 1 << digit;
-                        } else {
-                            return Err(de::Error::custom("Survival count out of range"));
-                        }
-                    } else {
-                        return Err(de::Error::custom("Invalid character in survival string"));
-                    }
-                }
-
-                for ch in born_part.chars() {
-                    if let Some(digit) = ch.to_digit(10) {
-                        if digit < 8 {
-                            rule_value |= 1 << (digit + 8);
-                        } else {
-                            return Err(de::Error::custom("Birth count out of range"));
-                        }
-                    } else {
-                        return Err(de::Error::custom("Invalid character in birth string"));
-                    }
-                }
-
-                Ok(Rule(rule_value))
+                Rule::parse(value).map_err(de::Error::custom)
             }
         }
 
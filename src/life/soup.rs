@@ -0,0 +1,109 @@
+//! Random fills ("soups"), optionally with symmetry.
+
+use crate::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// The symmetry of a soup, using the same names as apgsearch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SoupSymmetry {
+    /// No symmetry.
+    #[default]
+    C1,
+    /// Looks the same after a half turn.
+    C2,
+    /// Looks the same after a quarter turn.
+    C4,
+    /// Mirrored left to right and top to bottom.
+    D4,
+    /// Every rotation and flip.
+    D8,
+}
+
+impl SoupSymmetry {
+    fn transforms(self) -> &'static [Transform] {
+        use Transform::*;
+
+        match self {
+            SoupSymmetry::C1 => &[Identity],
+            SoupSymmetry::C2 => &[Identity, Rotate180],
+            SoupSymmetry::C4 => &[Identity, Rotate90, Rotate180, Rotate270],
+            SoupSymmetry::D4 => &[Identity, FlipX, FlipY, Rotate180],
+            SoupSymmetry::D8 => &Transform::ALL,
+        }
+    }
+    /// The next symmetry, for cycling through them with a key.
+    pub fn next(self) -> Self {
+        match self {
+            SoupSymmetry::C1 => SoupSymmetry::C2,
+            SoupSymmetry::C2 => SoupSymmetry::C4,
+            SoupSymmetry::C4 => SoupSymmetry::D4,
+            SoupSymmetry::D4 => SoupSymmetry::D8,
+            SoupSymmetry::D8 => SoupSymmetry::C1,
+        }
+    }
+}
+
+impl fmt::Display for SoupSymmetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Makes a random `width` x `height` grid where each cell is alive with a chance of `density`.
+///
+/// The symmetry is around the center of the grid. Rotating a grid that isn't square by a quarter
+/// turn puts some cells outside of it, those are just left out.
+pub fn random_soup(
+    width: usize,
+    height: usize,
+    density: f64,
+    symmetry: SoupSymmetry,
+    seed: u64,
+) -> Grid<bool> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let density = density.clamp(0., 1.);
+    let mut grid = Grid::new(height, width);
+
+    // Every cell is its own orbit, so there's nothing to remember. The cells are filled in the
+    // same order as below, so a seed gives the same soup either way.
+    if symmetry == SoupSymmetry::C1 {
+        for tile in grid.iter_mut() {
+            *tile = rng.gen_bool(density);
+        }
+        return grid;
+    }
+
+    // Coordinates are doubled so the center is at (0, 0) even when the size is even.
+    let to_centered = |x: usize, y: usize| {
+        (
+            2 * x as i32 - (width as i32 - 1),
+            2 * y as i32 - (height as i32 - 1),
+        )
+    };
+
+    // Every cell gets the same value as the rest of its orbit, which is decided the first time
+    // any of them is seen.
+    let mut orbits: HashMap<(i32, i32), bool> = HashMap::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            let (u, v) = to_centered(x, y);
+            let representative = symmetry
+                .transforms()
+                .iter()
+                .map(|t| t.apply(u, v))
+                .min()
+                .unwrap_or((u, v));
+
+            grid[(y, x)] = *orbits
+                .entry(representative)
+                .or_insert_with(|| rng.gen_bool(density));
+        }
+    }
+
+    grid
+}
@@ -0,0 +1,70 @@
+//! The eight symmetries of a square (the D8 group), used for rotating and flipping patterns.
+
+use grid::Grid;
+
+/// One of the eight ways a square can be rotated or flipped.
+///
+/// Rotations are counter clockwise, in the same coordinates as the board (y goes up the screen).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Transform {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipX,
+    FlipY,
+    Transpose,
+    AntiTranspose,
+}
+
+impl Transform {
+    /// Every transform in the group, starting with the identity.
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::FlipX,
+        Transform::FlipY,
+        Transform::Transpose,
+        Transform::AntiTranspose,
+    ];
+
+    /// Applies the transform to a coordinate, around the origin.
+    pub fn apply(self, x: i32, y: i32) -> (i32, i32) {
+        match self {
+            Transform::Identity => (x, y),
+            Transform::Rotate90 => (-y, x),
+            Transform::Rotate180 => (-x, -y),
+            Transform::Rotate270 => (y, -x),
+            Transform::FlipX => (-x, y),
+            Transform::FlipY => (x, -y),
+            Transform::Transpose => (y, x),
+            Transform::AntiTranspose => (-y, -x),
+        }
+    }
+    /// Applies the transform to a grid of cells. Quarter turns and transposes swap the width and
+    /// height.
+    pub fn apply_grid(self, grid: &Grid<bool>) -> Grid<bool> {
+        if grid.is_empty() {
+            return grid.clone();
+        }
+
+        // Where the far corner ends up tells us the new size, and how far to shift everything
+        // back so it starts at (0, 0).
+        let (w, h) = (grid.cols() as i32, grid.rows() as i32);
+        let (corner_x, corner_y) = self.apply(w - 1, h - 1);
+        let (min_x, min_y) = (corner_x.min(0), corner_y.min(0));
+
+        let mut transformed = Grid::new(
+            corner_y.unsigned_abs() as usize + 1,
+            corner_x.unsigned_abs() as usize + 1,
+        );
+        for ((y, x), tile) in grid.indexed_iter() {
+            let (nx, ny) = self.apply(x as i32, y as i32);
+            transformed[((ny - min_y) as usize, (nx - min_x) as usize)] = *tile;
+        }
+
+        transformed
+    }
+}
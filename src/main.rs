@@ -1,10 +1,16 @@
+use clap::Parser;
 use prelude::*;
 
+pub mod autosave;
+pub mod cli;
 pub mod config;
+pub mod formats;
 pub mod game_logic;
+pub mod library;
 pub mod life;
 pub mod prelude;
 pub mod savestates;
+pub mod search;
 #[cfg(test)]
 mod tests;
 pub mod timing;
@@ -12,5 +18,19 @@ pub mod ui;
 pub mod utils;
 
 fn main() {
-    nannou::app(model).update(update).view(view).run();
+    let args = cli::Args::parse();
+    if let Some(name) = args.print {
+        cli::print_savestate(name);
+        return;
+    }
+    if let Some(command) = args.command {
+        cli::run_headless(command);
+        return;
+    }
+
+    nannou::app(model)
+        .update(update)
+        .view(view)
+        .exit(end_session)
+        .run();
 }
@@ -1,13 +1,21 @@
+pub use crate::autosave::*;
 pub use crate::config::*;
+pub use crate::formats::*;
 pub use crate::game_logic::cache::*;
+pub use crate::game_logic::clipboard::*;
+pub use crate::game_logic::files::*;
+pub use crate::game_logic::jobs::*;
 pub use crate::game_logic::keybinds::*;
 pub use crate::game_logic::model::*;
 pub use crate::game_logic::mouse::*;
 pub use crate::game_logic::other_events::*;
+pub use crate::game_logic::paste::*;
 pub use crate::game_logic::selection::*;
+pub use crate::game_logic::selection_tools::*;
 pub use crate::game_logic::update::*;
 pub use crate::game_logic::utils::*;
 pub use crate::game_logic::view::*;
+pub use crate::library::*;
 pub use crate::life::*;
 pub use crate::savestates::*;
 pub use crate::time;
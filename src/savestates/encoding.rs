@@ -0,0 +1,201 @@
+//! The ways the cells in a savestate can be stored. Savestates from version 3 on pick whichever
+//! is smallest for the board, and store which one in the header.
+//!
+//! Cells are stored in the grid's order, bottom row first. Numbers are LEB128 varints (7 bits per
+//! byte, with the top bit set on every byte but the last), so small numbers take one byte.
+
+use crate::prelude::*;
+
+/// The most cells a savestate can have, the same as the biggest macrocell pattern that can go on
+/// the board. Anything bigger is a corrupt header, and would run out of memory.
+pub const MAX_CELLS: usize = (MAX_EXPANDED_SIZE * MAX_EXPANDED_SIZE) as usize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// One bit per cell. Best for busy boards.
+    Bits,
+    /// Lengths of the runs of dead and alive cells, starting with dead. Best for boards with big
+    /// empty areas and big solid ones.
+    Runs,
+    /// The gaps between live cells. Best for sparse boards.
+    Cells,
+}
+
+impl Encoding {
+    /// The encoding that stores these tiles in the fewest bytes.
+    pub fn choose(tiles: &Grid<bool>) -> Encoding {
+        let bits = (tiles.rows() * tiles.cols()).div_ceil(8);
+        let (mut runs, mut cells) = (0, 0);
+
+        let (mut run, mut alive) = (0, false);
+        let mut gap = 0;
+        for tile in tiles.iter() {
+            if *tile != alive {
+                runs += varint_len(run);
+                (run, alive) = (0, *tile);
+            }
+            run += 1;
+
+            if *tile {
+                cells += varint_len(gap);
+                gap = 0;
+            } else {
+                gap += 1;
+            }
+        }
+        runs += varint_len(run);
+
+        if bits <= runs && bits <= cells {
+            Encoding::Bits
+        } else if runs <= cells {
+            Encoding::Runs
+        } else {
+            Encoding::Cells
+        }
+    }
+    pub fn from_byte(byte: u8) -> Option<Encoding> {
+        match byte {
+            0 => Some(Encoding::Bits),
+            1 => Some(Encoding::Runs),
+            2 => Some(Encoding::Cells),
+            _ => None,
+        }
+    }
+    pub fn to_byte(self) -> u8 {
+        match self {
+            Encoding::Bits => 0,
+            Encoding::Runs => 1,
+            Encoding::Cells => 2,
+        }
+    }
+    pub fn encode(self, tiles: &Grid<bool>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        match self {
+            Encoding::Bits => {
+                bytes = vec![0; (tiles.rows() * tiles.cols()).div_ceil(8)];
+                for (i, tile) in tiles.iter().enumerate() {
+                    bytes[i / 8] |= (*tile as u8) << (i % 8);
+                }
+            }
+            Encoding::Runs => {
+                let (mut run, mut alive) = (0, false);
+                for tile in tiles.iter() {
+                    if *tile != alive {
+                        push_varint(&mut bytes, run);
+                        (run, alive) = (0, *tile);
+                    }
+                    run += 1;
+                }
+                push_varint(&mut bytes, run);
+            }
+            Encoding::Cells => {
+                let mut gap = 0;
+                for tile in tiles.iter() {
+                    if *tile {
+                        push_varint(&mut bytes, gap);
+                        gap = 0;
+                    } else {
+                        gap += 1;
+                    }
+                }
+            }
+        }
+
+        bytes
+    }
+    /// Turns some bytes back into a width x height grid.
+    pub fn decode(self, bytes: &[u8], width: usize, height: usize) -> Result<Grid<bool>, String> {
+        let len = width
+            .checked_mul(height)
+            .filter(|len| *len <= MAX_CELLS)
+            .ok_or("The board size is too big.")?;
+        if self == Encoding::Bits && bytes.len() != len.div_ceil(8) {
+            return Err("The wrong number of cells.".to_string());
+        }
+        let mut tiles = vec![false; len];
+        let mut varints = Varints { bytes };
+
+        match self {
+            Encoding::Bits => {
+                for (i, tile) in tiles.iter_mut().enumerate() {
+                    *tile = bytes[i / 8] >> (i % 8) & 1 == 1;
+                }
+            }
+            Encoding::Runs => {
+                let (mut i, mut alive) = (0, false);
+                while let Some(run) = varints.next().transpose()? {
+                    let end = i.saturating_add(run);
+                    tiles
+                        .get_mut(i..end)
+                        .ok_or("A run goes past the end of the board.")?
+                        .fill(alive);
+                    (i, alive) = (end, !alive);
+                }
+                if i != len {
+                    return Err("The wrong number of cells.".to_string());
+                }
+            }
+            Encoding::Cells => {
+                let mut i = 0;
+                while let Some(gap) = varints.next().transpose()? {
+                    i = i.saturating_add(gap);
+                    *tiles
+                        .get_mut(i)
+                        .ok_or("A cell is past the end of the board.")? = true;
+                    i += 1;
+                }
+            }
+        }
+
+        Ok(Grid::from_vec(tiles, width))
+    }
+}
+
+fn varint_len(mut n: usize) -> usize {
+    let mut len = 1;
+    while n >= 0x80 {
+        n >>= 7;
+        len += 1;
+    }
+
+    len
+}
+
+fn push_varint(bytes: &mut Vec<u8>, mut n: usize) {
+    while n >= 0x80 {
+        bytes.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+/// Reads varints one at a time.
+struct Varints<'a> {
+    bytes: &'a [u8],
+}
+
+impl Iterator for Varints<'_> {
+    type Item = Result<usize, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+
+        let mut n = 0usize;
+        for (i, byte) in self.bytes.iter().enumerate() {
+            let Some(bits) = ((byte & 0x7f) as usize).checked_shl(7 * i as u32) else {
+                return Some(Err("A number is too big.".to_string()));
+            };
+            n |= bits;
+
+            if byte & 0x80 == 0 {
+                self.bytes = &self.bytes[i + 1..];
+                return Some(Ok(n));
+            }
+        }
+
+        Some(Err("A number is cut off.".to_string()))
+    }
+}
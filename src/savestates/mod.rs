@@ -0,0 +1,540 @@
+//! Logic for saving and loading boards.
+//!
+//! A savestate file is MAGIC, then the format version as a little endian u16, then a byte saying
+//! how the cells are stored (see encoding.rs). After the header is the metadata and the size of
+//! the (cropped) board encoded with bincode, then the cells.
+//!
+//! Versions 1 and 2 were the whole SaveState encoded with bincode, with no encoding byte. Files from
+//! before the header existed are just a bincode Board, and are read as version 0.
+
+pub use encoding::*;
+
+mod encoding;
+
+use crate::prelude::*;
+use bitvec::prelude::*;
+use chrono::{DateTime, Local};
+use grid::Grid;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+impl Serialize for Board {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut board = self.clone();
+        board.crop();
+        let width = board.width();
+
+        let mut bv: BitVec<u8, Lsb0> = BitVec::new();
+        board.tiles.iter().for_each(|v| bv.push(*v));
+
+        let mut state = serializer.serialize_struct("Board", 4)?;
+        state.serialize_field("width", &width)?;
+        state.serialize_field("tiles", &bv)?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct BoardData {
+            width: usize,
+            tiles: BitVec<u8, Lsb0>,
+        }
+
+        let data = BoardData::deserialize(deserializer)?;
+
+        let len = data.tiles.len();
+        if len > 0 && (data.width == 0 || len % data.width != 0) {
+            return Err(serde::de::Error::custom(format!(
+                "{} cells don't fit in rows of {}.",
+                len, data.width
+            )));
+        }
+
+        let tiles: Vec<bool> = data.tiles.iter().by_vals().collect();
+
+        Ok(Board {
+            tiles: Grid::from_vec(tiles, data.width),
+        })
+    }
+}
+
+/// A name for a file, based on the current time. These sort in the order they were made, and
+/// only use characters that work on every filesystem.
+pub fn timestamp_id() -> String {
+    Local::now().format("%Y-%m-%d_%H-%M-%S").to_string()
+}
+
+/// `name`, or `name-2`, `name-3`, etc. if a file with that name and extension is already in `dir`.
+/// An empty extension checks for a folder instead.
+pub fn unique_name(dir: &str, name: &str, extension: &str) -> String {
+    let taken = |name: &str| match extension {
+        "" => Path::new(&format!("{}/{}", dir, name)).exists(),
+        _ => Path::new(&format!("{}/{}.{}", dir, name, extension)).exists(),
+    };
+
+    if !taken(name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|i| format!("{}-{}", name, i))
+        .find(|name| !taken(name))
+        .unwrap()
+}
+
+/// The start of every savestate file.
+const MAGIC: &[u8; 8] = b"GOLSTATE";
+
+/// The current savestate format version. Bump this whenever SaveState changes, and add a case to
+/// SaveState::from_bytes that reads the old version.
+pub const SAVESTATE_VERSION: u16 = 3;
+
+/// Something that went wrong saving or loading a savestate.
+#[derive(Debug)]
+pub enum SaveError {
+    /// There's no savestate with this name.
+    NotFound(String),
+    /// The file isn't a savestate, or it's been cut short.
+    Corrupt(String),
+    /// The savestate is from a newer version of the game.
+    UnsupportedVersion(u16),
+    /// There's already a savestate with this name.
+    AlreadyExists(String),
+    /// The name can't be used as a file name.
+    InvalidName(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::NotFound(name) => write!(f, "There's no savestate called '{}'.", name),
+            SaveError::Corrupt(e) => write!(f, "The savestate is corrupt: {}", e),
+            SaveError::UnsupportedVersion(version) => write!(
+                f,
+                "The savestate is version {}, but only versions up to {} are supported.",
+                version, SAVESTATE_VERSION
+            ),
+            SaveError::AlreadyExists(name) => {
+                write!(f, "There's already a savestate called '{}'.", name)
+            }
+            SaveError::InvalidName(name) => write!(f, "'{}' isn't a valid name.", name),
+            SaveError::Io(e) => write!(f, "Failed to access the savestate: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+impl From<bincode::Error> for SaveError {
+    fn from(e: bincode::Error) -> Self {
+        SaveError::Corrupt(e.to_string())
+    }
+}
+
+/// Everything about a savestate other than the board.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Metadata {
+    pub name: String,
+    pub description: String,
+    pub rule: Rule,
+    pub generation: u64,
+    /// Where the bottom left corner of the (cropped) saved board was on the board.
+    pub offset: (i64, i64),
+    /// The size of the board, if CONFIG.keep_savestate_layout was on when it was saved.
+    pub board_size: Option<(usize, usize)>,
+    pub camera_offset: (f32, f32),
+    /// When the savestate was made, in RFC 3339 format.
+    pub created: String,
+}
+
+/// A board and its metadata, which is what gets written to a .gol file.
+#[derive(Clone, Debug)]
+pub struct SaveState {
+    pub metadata: Metadata,
+    pub board: Board,
+}
+
+impl SaveState {
+    /// Takes a snapshot of the board in a model.
+    pub fn from_model(model: &Model, name: String) -> Self {
+        let offset = Pattern::from_grid(&model.board.tiles)
+            .bounds()
+            .map_or((0, 0), |((x, y), _)| (x as i64, y as i64));
+
+        Self {
+            metadata: Metadata {
+                name,
+                description: String::new(),
+                rule: current_rule(),
+                generation: model.generation,
+                offset,
+                board_size: CONFIG.keep_savestate_layout.then(|| model.board.wh()),
+                camera_offset: model.cache.target_camera_offset,
+                created: Local::now().to_rfc3339(),
+            },
+            board: model.board.clone(),
+        }
+    }
+    /// The cells as they were on the board if the layout was saved, otherwise cropped.
+    pub fn tiles(&self) -> Grid<bool> {
+        let Some((width, height)) = self.metadata.board_size else {
+            return self.board.tiles.clone();
+        };
+        let (dx, dy) = self.metadata.offset;
+
+        let mut board = Board::new(width, height);
+        for ((y, x), alive) in self.board.tiles.indexed_iter() {
+            let (x, y) = (x as i64 + dx, y as i64 + dy);
+            if *alive && x >= 0 && y >= 0 {
+                board.set(x as usize, y as usize, true);
+            }
+        }
+
+        board.tiles
+    }
+    /// Writes the savestate, with the cells in whichever encoding is smallest.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut board = self.board.clone();
+        board.crop();
+        let encoding = Encoding::choose(&board.tiles);
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(SAVESTATE_VERSION.to_le_bytes());
+        bytes.push(encoding.to_byte());
+        let (width, height) = (board.width() as u64, board.height() as u64);
+        bytes.extend(bincode::serialize(&(&self.metadata, width, height)).unwrap());
+        bytes.extend(encoding.encode(&board.tiles));
+
+        bytes
+    }
+    /// Reads a savestate file, from any version up to SAVESTATE_VERSION.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SaveError> {
+        let (version, data) = match bytes.strip_prefix(MAGIC) {
+            Some([a, b, data @ ..]) => (u16::from_le_bytes([*a, *b]), data),
+            Some(_) => return Err(SaveError::Corrupt("the version is missing.".to_string())),
+            None => (0, bytes),
+        };
+
+        match version {
+            // Just the board, so fill in what we can.
+            0 => {
+                let board: Board = bincode::deserialize(data)?;
+                Ok(Self {
+                    metadata: Metadata {
+                        name: String::new(),
+                        description: String::new(),
+                        rule: current_rule(),
+                        generation: 0,
+                        offset: (0, 0),
+                        board_size: None,
+                        camera_offset: (0., 0.),
+                        created: String::new(),
+                    },
+                    board,
+                })
+            }
+            1 => Ok(bincode::deserialize::<old::SaveStateV1>(data)?.into()),
+            2 => Ok(bincode::deserialize::<old::SaveStateV2>(data)?.into()),
+            SAVESTATE_VERSION => {
+                let (encoding, mut data) = data
+                    .split_first()
+                    .ok_or(SaveError::Corrupt("the encoding is missing.".to_string()))?;
+                let encoding = Encoding::from_byte(*encoding).ok_or(SaveError::Corrupt(
+                    format!("unknown encoding {}.", encoding),
+                ))?;
+
+                let (metadata, width, height): (Metadata, u64, u64) =
+                    bincode::deserialize_from(&mut data)?;
+                let tiles = encoding
+                    .decode(data, width as usize, height as usize)
+                    .map_err(SaveError::Corrupt)?;
+
+                Ok(Self {
+                    metadata,
+                    board: Board { tiles },
+                })
+            }
+            version => Err(SaveError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+/// Saves the board to a new savestate named after the current time, returning the name.
+pub fn save_board(model: &Model) -> Result<String, SaveError> {
+    let name = unique_name(&savestate_dir(), &timestamp_id(), "gol");
+    write_savestate(model, &name, false)?;
+
+    Ok(name)
+}
+
+/// Saves the board to the savestate called `name`. Fails if there's already one with that name,
+/// unless `overwrite` is true.
+pub fn write_savestate(model: &Model, name: &str, overwrite: bool) -> Result<(), SaveError> {
+    check_name(name)?;
+    if !overwrite && Path::new(&savestate_path(name)).exists() {
+        return Err(SaveError::AlreadyExists(name.to_string()));
+    }
+
+    let serialized = SaveState::from_model(model, name.to_string()).to_bytes();
+    fs::create_dir_all(savestate_dir())?;
+    fs::write(savestate_path(name), serialized)?;
+
+    Ok(())
+}
+
+/// Loads a savestate onto the board, remembering its name so Ctrl+S saves back to it.
+pub fn open_savestate(model: &mut Model, name: String) -> Result<(), SaveError> {
+    let savestate = load_savestate(name.clone())?;
+    apply_savestate(model, &savestate);
+    model.savestate_name = Some(name);
+
+    Ok(())
+}
+
+/// Savestate names become file names, so they can't be empty or have slashes.
+pub fn check_name(name: &str) -> Result<(), SaveError> {
+    if name.trim().is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(SaveError::InvalidName(name.to_string()));
+    }
+
+    Ok(())
+}
+
+pub fn load_savestate(id: String) -> Result<SaveState, SaveError> {
+    let bytes = fs::read(savestate_path(&id)).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => SaveError::NotFound(id),
+        _ => SaveError::Io(e),
+    })?;
+
+    SaveState::from_bytes(&bytes)
+}
+
+/// A savestate in the savestates folder, as listed by the savestate browser.
+#[derive(Clone, Debug)]
+pub struct SavestateEntry {
+    /// The file name, without .gol.
+    pub name: String,
+    /// Size of the board, or of the pattern if the layout wasn't saved.
+    pub size: (usize, usize),
+    pub population: usize,
+    /// When it was saved, as "year-month-day hour:minute".
+    pub date: String,
+    /// A shrunk down copy of the board, bottom row first.
+    pub thumbnail: Grid<bool>,
+}
+
+/// Every savestate in the savestates folder, newest first. Ones that can't be read are skipped.
+pub fn list_savestates(thumbnail_size: usize) -> Vec<SavestateEntry> {
+    let Ok(dir) = fs::read_dir(savestate_dir()) else {
+        return Vec::new();
+    };
+
+    let mut entries: Vec<(SystemTime, SavestateEntry)> = dir
+        .flatten()
+        .filter_map(|file| {
+            let path = file.path();
+            if path.extension()? != "gol" {
+                return None;
+            }
+
+            let modified = file.metadata().and_then(|m| m.modified()).ok()?;
+            let savestate = SaveState::from_bytes(&fs::read(&path).ok()?).ok()?;
+            let tiles = &savestate.board.tiles;
+
+            let entry = SavestateEntry {
+                name: path.file_stem()?.to_string_lossy().to_string(),
+                size: savestate
+                    .metadata
+                    .board_size
+                    .unwrap_or((tiles.cols(), tiles.rows())),
+                population: tiles.iter().filter(|alive| **alive).count(),
+                date: DateTime::<Local>::from(modified)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string(),
+                thumbnail: thumbnail(tiles, thumbnail_size),
+            };
+            Some((modified, entry))
+        })
+        .collect();
+
+    entries.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    entries.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// Shrinks some tiles to fit in a size x size square, keeping the aspect ratio. A cell in the
+/// thumbnail is alive if any of the cells it covers are.
+pub fn thumbnail(tiles: &Grid<bool>, size: usize) -> Grid<bool> {
+    let scale = tiles.cols().max(tiles.rows()).div_ceil(size).max(1);
+    let mut thumbnail = Grid::new(tiles.rows().div_ceil(scale), tiles.cols().div_ceil(scale));
+
+    for ((y, x), alive) in tiles.indexed_iter() {
+        if *alive {
+            thumbnail[(y / scale, x / scale)] = true;
+        }
+    }
+
+    thumbnail
+}
+
+/// Renames a savestate, without overwriting another one.
+pub fn rename_savestate(from: &str, to: &str) -> Result<(), SaveError> {
+    check_name(to)?;
+    if Path::new(&savestate_path(to)).exists() {
+        return Err(SaveError::AlreadyExists(to.to_string()));
+    }
+
+    let mut savestate = load_savestate(from.to_string())?;
+    savestate.metadata.name = to.to_string();
+    fs::write(savestate_path(to), savestate.to_bytes())?;
+    fs::remove_file(savestate_path(from))?;
+
+    Ok(())
+}
+
+pub fn delete_savestate(name: &str) -> Result<(), SaveError> {
+    fs::remove_file(savestate_path(name)).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => SaveError::NotFound(name.to_string()),
+        _ => SaveError::Io(e),
+    })
+}
+
+/// Puts a savestate on the board, along with its rule, generation and camera position.
+pub fn apply_savestate(model: &mut Model, savestate: &SaveState) {
+    let metadata = &savestate.metadata;
+
+    apply_pattern_rule(Some(metadata.rule));
+    match metadata.board_size {
+        Some(_) => replace_board(
+            model,
+            Board {
+                tiles: savestate.tiles(),
+            },
+        ),
+        None => load_into_board(model, &savestate.board.tiles),
+    }
+    model.generation = metadata.generation;
+    model.cache.target_camera_offset = metadata.camera_offset;
+    clamp_camera(model);
+}
+
+/// Replaces the board with some tiles, centered. The board grows if they don't fit.
+pub fn load_into_board(model: &mut Model, tiles: &Grid<bool>) {
+    let board = fit_board(tiles, model.board.wh());
+    replace_board(model, board);
+}
+
+/// A board at least `(width, height)` big, and big enough for the tiles, with the tiles centered.
+pub fn fit_board(tiles: &Grid<bool>, (width, height): (usize, usize)) -> Board {
+    let (width, height) = (width.max(tiles.cols()), height.max(tiles.rows()));
+
+    let mut board = Board {
+        tiles: tiles.clone(),
+    };
+    board.set_wh(width, height);
+    board
+}
+
+/// Swaps in a new board, fitting the camera to it.
+pub fn replace_board(model: &mut Model, board: Board) {
+    model.board = board;
+
+    model.cache.update(model.board.wh(), CONFIG.tile_size);
+    model.selection = None;
+    clamp_camera(model);
+}
+
+/// Switches to the rule a pattern file asked for, if it's different.
+pub fn apply_pattern_rule(rule: Option<Rule>) {
+    if let Some(rule) = rule {
+        if rule != current_rule() {
+            set_rule(rule);
+            notify_info(format!("Rule changed to {}.", rule.to_bs()));
+        }
+    }
+}
+
+fn savestate_dir() -> String {
+    BASE_DIR.to_string() + "/savestates"
+}
+
+fn savestate_path(name: &str) -> String {
+    format!("{}/{}.gol", savestate_dir(), name)
+}
+
+/// Older savestate versions, so they can still be loaded.
+mod old {
+    use super::*;
+
+    /// Version 1, from before the board size could be saved.
+    #[derive(Deserialize)]
+    pub struct SaveStateV1 {
+        metadata: MetadataV1,
+        board: Board,
+    }
+
+    #[derive(Deserialize)]
+    struct MetadataV1 {
+        name: String,
+        description: String,
+        rule: Rule,
+        generation: u64,
+        offset: (i64, i64),
+        camera_offset: (f32, f32),
+        created: String,
+    }
+
+    /// Version 2, from before the cells could be compressed.
+    #[derive(Deserialize)]
+    pub struct SaveStateV2 {
+        metadata: Metadata,
+        board: Board,
+    }
+
+    impl From<SaveStateV2> for SaveState {
+        fn from(old: SaveStateV2) -> Self {
+            SaveState {
+                metadata: old.metadata,
+                board: old.board,
+            }
+        }
+    }
+
+    impl From<SaveStateV1> for SaveState {
+        fn from(old: SaveStateV1) -> Self {
+            let metadata = old.metadata;
+
+            SaveState {
+                metadata: Metadata {
+                    name: metadata.name,
+                    description: metadata.description,
+                    rule: metadata.rule,
+                    generation: metadata.generation,
+                    offset: metadata.offset,
+                    board_size: None,
+                    camera_offset: metadata.camera_offset,
+                    created: metadata.created,
+                },
+                board: old.board,
+            }
+        }
+    }
+}
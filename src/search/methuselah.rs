@@ -0,0 +1,282 @@
+//! Looks for methuselahs: small seeds that take a long time to settle down.
+//!
+//! Seeds are either every arrangement of up to N cells in a box, or random samples of them. Each
+//! one is run until it stabilizes, and the longest lived ones are kept in a leaderboard file.
+
+use crate::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+
+/// The longest period a pattern can settle into and still be counted as stable.
+const MAX_PERIOD: usize = 30;
+
+/// How many generations the population has to repeat for before we believe it has settled.
+const CONFIRM_GENERATIONS: usize = MAX_PERIOD * 4;
+
+/// Options for the methuselah search, passed in from the command line.
+#[derive(clap::Args, Debug)]
+pub struct MethuselahOptions {
+    /// Width of the box the seeds are placed in.
+    #[arg(long, default_value_t = 5)]
+    pub width: usize,
+
+    /// Height of the box the seeds are placed in.
+    #[arg(long, default_value_t = 5)]
+    pub height: usize,
+
+    /// The most live cells a seed can have.
+    #[arg(long, default_value_t = 5)]
+    pub cells: usize,
+
+    /// Try this many random seeds, instead of going through every possible one.
+    #[arg(long)]
+    pub samples: Option<usize>,
+
+    /// Seed for the random number generator, so a random search can be repeated.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Give up on a seed if it hasn't stabilized after this many generations.
+    #[arg(long, default_value_t = 20_000)]
+    pub max_generations: usize,
+
+    /// How many seeds to keep in the leaderboard.
+    #[arg(long, default_value_t = 50)]
+    pub top: usize,
+}
+
+/// How long a seed lived, and what was left of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Lifespan {
+    pub generations: usize,
+    pub final_population: usize,
+}
+
+/// One seed in the leaderboard file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub lifespan: usize,
+    pub final_population: usize,
+    pub rule: String,
+    pub cells: Vec<(i32, i32)>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Leaderboard {
+    entries: Vec<LeaderboardEntry>,
+}
+
+/// Runs a pattern until it stabilizes.
+///
+/// A pattern counts as stable once its population has been periodic (with a period up to
+/// MAX_PERIOD) for CONFIRM_GENERATIONS generations. Only looking at the population means that
+/// gliders flying off forever don't stop a pattern from stabilizing, which is how lifespans are
+/// normally counted.
+///
+/// Returns None if it's still going after `max_generations`.
+pub fn measure_lifespan(
+    pattern: &Pattern,
+    rule: &Rule,
+    max_generations: usize,
+) -> Option<Lifespan> {
+    let mut pattern = pattern.clone();
+    let mut populations = vec![pattern.population()];
+
+    for generation in 1..=max_generations + CONFIRM_GENERATIONS {
+        pattern = pattern.step(rule);
+        populations.push(pattern.population());
+
+        if generation < CONFIRM_GENERATIONS + MAX_PERIOD {
+            continue;
+        }
+
+        if let Some(start) = settled_at(&populations) {
+            if start > max_generations {
+                return None;
+            }
+
+            return Some(Lifespan {
+                generations: start,
+                final_population: populations[start],
+            });
+        }
+    }
+
+    None
+}
+
+/// If the end of the population history is periodic, returns the generation it became periodic.
+fn settled_at(populations: &[usize]) -> Option<usize> {
+    let last = populations.len() - 1;
+
+    (1..=MAX_PERIOD)
+        .filter(|&period| {
+            (last - CONFIRM_GENERATIONS..=last).all(|i| populations[i] == populations[i - period])
+        })
+        .map(|period| {
+            let mut i = last - CONFIRM_GENERATIONS;
+            while i >= period && populations[i] == populations[i - period] {
+                i -= 1;
+            }
+            (i + 1).saturating_sub(period)
+        })
+        .min()
+}
+
+/// Every seed with up to `max_cells` cells in a `width` x `height` box, without duplicates.
+fn enumerate_seeds(width: usize, height: usize, max_cells: usize) -> HashSet<Pattern> {
+    let positions: Vec<(i32, i32)> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x as i32, y as i32)))
+        .collect();
+    let mut seeds = HashSet::new();
+
+    for count in 1..=max_cells.min(positions.len()) {
+        let mut indices: Vec<usize> = (0..count).collect();
+
+        loop {
+            seeds.insert(Pattern::new(indices.iter().map(|&i| positions[i])).canonical());
+
+            // Move on to the next combination, like counting but with no repeated digits.
+            let Some(i) = (0..count)
+                .rev()
+                .find(|&i| indices[i] < positions.len() - count + i)
+            else {
+                break;
+            };
+            indices[i] += 1;
+            for j in i + 1..count {
+                indices[j] = indices[j - 1] + 1;
+            }
+        }
+    }
+
+    seeds
+}
+
+/// Random seeds with up to `max_cells` cells in a `width` x `height` box, without duplicates.
+fn sample_seeds(
+    width: usize,
+    height: usize,
+    max_cells: usize,
+    samples: usize,
+    rng: &mut StdRng,
+) -> HashSet<Pattern> {
+    let area = width * height;
+    let max_cells = max_cells.min(area);
+
+    (0..samples)
+        .map(|_| {
+            let count = rng.gen_range(1..=max_cells);
+            let cells = rand::seq::index::sample(rng, area, count)
+                .into_iter()
+                .map(|i| ((i % width) as i32, (i / width) as i32));
+
+            Pattern::new(cells).canonical()
+        })
+        .collect()
+}
+
+/// Runs the search from the command line, printing the results and saving the leaderboard.
+pub fn run(options: MethuselahOptions) {
+    if options.width == 0 || options.height == 0 || options.cells == 0 {
+        eprintln!("The box and the number of cells must be bigger than 0.");
+        return;
+    }
+
+    let rule = &current_rule();
+
+    let seeds = match options.samples {
+        Some(samples) => {
+            let seed = options.seed.unwrap_or_else(rand::random);
+            println!("Sampling {} random seeds (rng seed {}).", samples, seed);
+            let mut rng = StdRng::seed_from_u64(seed);
+            sample_seeds(
+                options.width,
+                options.height,
+                options.cells,
+                samples,
+                &mut rng,
+            )
+        }
+        None => enumerate_seeds(options.width, options.height, options.cells),
+    };
+
+    println!(
+        "Testing {} distinct seeds under {}.",
+        fmt_num(seeds.len()),
+        rule.serialize()
+    );
+
+    let results: Vec<LeaderboardEntry> = seeds
+        .par_iter()
+        .filter_map(|seed| {
+            let lifespan = measure_lifespan(seed, rule, options.max_generations)?;
+
+            Some(LeaderboardEntry {
+                lifespan: lifespan.generations,
+                final_population: lifespan.final_population,
+                rule: rule.serialize(),
+                cells: seed.cells().to_vec(),
+            })
+        })
+        .collect();
+
+    let unstable = seeds.len() - results.len();
+    if unstable > 0 {
+        println!(
+            "{} seeds didn't stabilize within {} generations.",
+            fmt_num(unstable),
+            options.max_generations
+        );
+    }
+
+    let mut leaderboard = load_leaderboard();
+    leaderboard.entries.extend(results);
+    leaderboard.entries.sort_by(|a, b| {
+        b.lifespan
+            .cmp(&a.lifespan)
+            .then(b.final_population.cmp(&a.final_population))
+    });
+
+    let mut seen = HashSet::new();
+    leaderboard
+        .entries
+        .retain(|entry| seen.insert((entry.rule.clone(), entry.cells.clone())));
+    leaderboard.entries.truncate(options.top);
+
+    for (i, entry) in leaderboard.entries.iter().enumerate() {
+        println!(
+            "{:>3}. {} generations, final population {} ({})",
+            i + 1,
+            entry.lifespan,
+            entry.final_population,
+            entry.rule
+        );
+        print_grid(Pattern::new(entry.cells.iter().copied()).to_grid());
+    }
+
+    save_leaderboard(&leaderboard);
+    println!("Leaderboard saved to {}.", leaderboard_path());
+}
+
+fn leaderboard_path() -> String {
+    BASE_DIR.to_string() + "/methuselahs.toml"
+}
+
+fn load_leaderboard() -> Leaderboard {
+    fs::read_to_string(leaderboard_path())
+        .ok()
+        .and_then(|text| toml::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_leaderboard(leaderboard: &Leaderboard) {
+    let _ = fs::create_dir_all(BASE_DIR.as_str());
+    let text = toml::to_string(leaderboard).unwrap();
+    fs::write(leaderboard_path(), text)
+        .unwrap_or_else(|e| eprintln!("Failed to save leaderboard: {}", e));
+}
@@ -0,0 +1,5 @@
+//! Searches for interesting patterns. These are slow, so they're mostly run from the command line.
+
+pub mod methuselah;
+pub mod periodic;
+pub mod predecessor;
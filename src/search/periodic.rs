@@ -0,0 +1,374 @@
+//! Searches for oscillators and spaceships, in the style of lifesrc.
+//!
+//! The unknowns are every cell in a box, in every generation of one period. Generation p has to be
+//! generation 0 moved by the displacement (0, 0 for an oscillator), and cells outside the box have
+//! to stay dead. Cells are filled in one at a time, and the search backs up as soon as any cell
+//! can't possibly evolve into what the next generation says it should be.
+//!
+//! The whole state of the search is the list of cells, so it can be saved to a file and resumed
+//! later.
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// How often the command line search saves its progress.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Options for the oscillator/spaceship search, passed in from the command line.
+#[derive(clap::Args, Debug)]
+pub struct PeriodicOptions {
+    /// The period to look for.
+    #[arg(long, default_value_t = 3)]
+    pub period: usize,
+
+    /// How far the pattern moves right each period (0 for oscillators).
+    #[arg(long, default_value_t = 0, allow_hyphen_values = true)]
+    pub dx: i32,
+
+    /// How far the pattern moves up each period (0 for oscillators).
+    #[arg(long, default_value_t = 0, allow_hyphen_values = true)]
+    pub dy: i32,
+
+    /// Width of the box the pattern has to fit in.
+    #[arg(long, default_value_t = 8)]
+    pub width: usize,
+
+    /// Height of the box the pattern has to fit in.
+    #[arg(long, default_value_t = 8)]
+    pub height: usize,
+
+    /// Where to save the progress of the search. Defaults to BASE_DIR/searches/search.toml.
+    #[arg(long)]
+    pub state: Option<String>,
+
+    /// Resume a search from a state file, instead of starting a new one.
+    #[arg(long)]
+    pub resume: Option<String>,
+
+    /// Keep going after the first result.
+    #[arg(long)]
+    pub all: bool,
+}
+
+/// How a call to PeriodicSearch::next ended.
+pub enum SearchResult {
+    /// Generation 0 of a pattern that works.
+    Found(Pattern),
+    /// There's nothing (else) to find.
+    Exhausted,
+    Cancelled,
+}
+
+/// The state of a search, which can be saved to and loaded from a file.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PeriodicSearch {
+    pub rule: String,
+    pub period: usize,
+    pub dx: i32,
+    pub dy: i32,
+    pub width: usize,
+    pub height: usize,
+    /// Every cell in every generation, as '-' (unknown), '0' (dead) or '1' (alive).
+    cells: String,
+    index: usize,
+    #[serde(skip)]
+    values: Vec<Option<bool>>,
+}
+
+impl PeriodicSearch {
+    pub fn new(rule: &Rule, period: usize, dx: i32, dy: i32, width: usize, height: usize) -> Self {
+        Self {
+            rule: rule.serialize(),
+            period,
+            dx,
+            dy,
+            width,
+            height,
+            cells: String::new(),
+            index: 0,
+            values: vec![None; period * width * height],
+        }
+    }
+    pub fn load(path: &str) -> Option<Self> {
+        let text = fs::read_to_string(path).ok()?;
+        let mut search: Self = toml::from_str(&text).ok()?;
+
+        search.values = search
+            .cells
+            .chars()
+            .map(|c| match c {
+                '0' => Some(false),
+                '1' => Some(true),
+                _ => None,
+            })
+            .collect();
+
+        (search.values.len() == search.period * search.width * search.height).then_some(search)
+    }
+    pub fn save(&mut self, path: &str) {
+        self.cells = self
+            .values
+            .iter()
+            .map(|v| match v {
+                Some(false) => '0',
+                Some(true) => '1',
+                None => '-',
+            })
+            .collect();
+
+        let text = toml::to_string(self).unwrap();
+        fs::write(path, text).unwrap_or_else(|e| eprintln!("Failed to save search state: {}", e));
+    }
+    fn var(&self, t: usize, x: usize, y: usize) -> usize {
+        (y * self.width + x) * self.period + t
+    }
+    /// The value of a cell in generation t (which can be one past the end). Cells outside the box
+    /// are always dead.
+    fn get(&self, t: usize, x: i32, y: i32) -> Option<bool> {
+        let (t, x, y) = if t == self.period {
+            (0, x - self.dx, y - self.dy)
+        } else {
+            (t, x, y)
+        };
+
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            return Some(false);
+        }
+
+        self.values[self.var(t, x as usize, y as usize)]
+    }
+    /// Whether cell (x, y) in generation t can still turn into its value in generation t + 1.
+    fn feasible(&self, rule: &Rule, t: usize, x: i32, y: i32) -> bool {
+        let Some(wanted) = self.get(t + 1, x, y) else {
+            return true;
+        };
+
+        let mut alive = 0;
+        let mut unknown = 0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                match self.get(t, x + dx, y + dy) {
+                    Some(true) => alive += 1,
+                    Some(false) => (),
+                    None => unknown += 1,
+                }
+            }
+        }
+
+        let centers: &[bool] = match self.get(t, x, y) {
+            Some(true) => &[true],
+            Some(false) => &[false],
+            None => &[false, true],
+        };
+
+        centers.iter().any(|&center| {
+            (alive..=alive + unknown).any(|count| {
+                let next = if center {
+                    rule.survive(count)
+                } else {
+                    rule.born(count)
+                };
+                next == wanted
+            })
+        })
+    }
+    /// Checks everything that depends on the variable at index i.
+    fn consistent(&self, rule: &Rule, i: usize) -> bool {
+        let t = i % self.period;
+        let cell = i / self.period;
+        let (x, y) = ((cell % self.width) as i32, (cell / self.width) as i32);
+
+        // The cells around it, in its own generation.
+        let neighbors_ok =
+            (-1..=1).all(|dy| (-1..=1).all(|dx| self.feasible(rule, t, x + dx, y + dy)));
+
+        // The cell that it comes from, in the generation before.
+        let parent_ok = if t == 0 {
+            self.feasible(rule, self.period - 1, x + self.dx, y + self.dy)
+        } else {
+            self.feasible(rule, t - 1, x, y)
+        };
+
+        neighbors_ok && parent_ok
+    }
+    /// Generation t of a fully filled in search.
+    fn generation(&self, t: usize) -> Pattern {
+        Pattern::new(
+            (0..self.height)
+                .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+                .filter(|&(x, y)| self.values[self.var(t, x, y)] == Some(true))
+                .map(|(x, y)| (x as i32, y as i32)),
+        )
+    }
+    /// Whether a filled in search is something new: not empty, and not repeating any sooner than
+    /// the period.
+    fn is_interesting(&self) -> bool {
+        let first = self.generation(0);
+        if first.is_empty() {
+            return false;
+        }
+
+        (1..self.period)
+            .filter(|q| self.period.is_multiple_of(*q))
+            .filter(|q| (self.dx * *q as i32) % self.period as i32 == 0)
+            .filter(|q| (self.dy * *q as i32) % self.period as i32 == 0)
+            .all(|q| {
+                let (dx, dy) = (
+                    self.dx * q as i32 / self.period as i32,
+                    self.dy * q as i32 / self.period as i32,
+                );
+                self.generation(q) != first.translate(dx, dy)
+            })
+    }
+    /// Keeps searching until a result is found, the search is finished, or `cancel` is set.
+    ///
+    /// Calling it again after a result carries on from where it left off. `checkpoint` is called
+    /// every now and then, so the progress can be saved.
+    pub fn next(
+        &mut self,
+        rule: &Rule,
+        cancel: &AtomicBool,
+        mut checkpoint: impl FnMut(&mut Self),
+    ) -> SearchResult {
+        let len = self.values.len();
+        if len == 0 {
+            return SearchResult::Exhausted;
+        }
+
+        // We stopped on a result last time, so move past it.
+        if self.index == len {
+            self.index -= 1;
+        }
+
+        let mut steps: usize = 0;
+
+        loop {
+            let i = self.index;
+
+            if i == len {
+                if self.is_interesting() {
+                    return SearchResult::Found(self.generation(0));
+                }
+                self.index -= 1;
+                continue;
+            }
+
+            steps += 1;
+            if steps.is_multiple_of(1 << 16) {
+                if cancel.load(Ordering::Relaxed) {
+                    return SearchResult::Cancelled;
+                }
+                checkpoint(self);
+            }
+
+            // Try dead first, then alive, then give up on this cell and back up.
+            self.values[i] = match self.values[i] {
+                None => Some(false),
+                Some(false) => Some(true),
+                Some(true) => {
+                    self.values[i] = None;
+                    if i == 0 {
+                        return SearchResult::Exhausted;
+                    }
+                    self.index -= 1;
+                    continue;
+                }
+            };
+
+            if self.consistent(rule, i) {
+                self.index += 1;
+            }
+        }
+    }
+}
+
+/// Runs the search from the command line, printing anything it finds.
+pub fn run(options: PeriodicOptions) {
+    let rule = &current_rule();
+
+    let state_path = options
+        .state
+        .clone()
+        .or(options.resume.clone())
+        .unwrap_or_else(|| BASE_DIR.to_string() + "/searches/search.toml");
+
+    let mut search = if let Some(path) = &options.resume {
+        let Some(search) = PeriodicSearch::load(path) else {
+            eprintln!("Failed to load search state from {}.", path);
+            return;
+        };
+        if search.rule != rule.serialize() {
+            eprintln!(
+                "That search was started with rule {}, but the current rule is {}.",
+                search.rule,
+                rule.serialize()
+            );
+            return;
+        }
+        println!("Resuming search from {}.", path);
+        search
+    } else {
+        if options.period == 0 || options.width == 0 || options.height == 0 {
+            eprintln!("The period and the box must be bigger than 0.");
+            return;
+        }
+        PeriodicSearch::new(
+            rule,
+            options.period,
+            options.dx,
+            options.dy,
+            options.width,
+            options.height,
+        )
+    };
+
+    if let Some(dir) = std::path::Path::new(&state_path).parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+
+    println!(
+        "Searching for period {} with displacement ({}, {}) in a {}x{} box, under {}.",
+        search.period, search.dx, search.dy, search.width, search.height, search.rule
+    );
+
+    let never = AtomicBool::new(false);
+    let mut last_checkpoint = Instant::now();
+    let mut found = HashSet::new();
+
+    loop {
+        let result = search.next(rule, &never, |search| {
+            if last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL {
+                search.save(&state_path);
+                last_checkpoint = Instant::now();
+            }
+        });
+        search.save(&state_path);
+
+        match result {
+            SearchResult::Found(pattern) => {
+                // The same object turns up in every position and orientation that fits the box.
+                if !found.insert(pattern.canonical()) {
+                    continue;
+                }
+                println!("Found:");
+                print_grid(pattern.to_grid());
+
+                if !options.all {
+                    println!("Run with --resume {} to keep looking.", state_path);
+                    break;
+                }
+            }
+            SearchResult::Exhausted | SearchResult::Cancelled => {
+                println!("Search finished, {} found.", found.len());
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Finds a parent pattern that evolves into a given pattern in one generation.
+//!
+//! If there isn't one, the pattern is a Garden of Eden: it can only ever exist if you draw it.
+//!
+//! The parent can only be one cell bigger than the target on each side, since cells further away
+//! can't affect the target. The search fills in the parent cell by cell, row by row, and backs up
+//! as soon as some target cell can't end up in the right state any more.
+
+use crate::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// How the search ended.
+pub enum PredecessorResult {
+    /// A parent was found. It's two cells wider and taller than the target.
+    Found(Grid<bool>),
+    /// Every possible parent was tried and none of them worked.
+    GardenOfEden,
+    Cancelled,
+}
+
+/// Searches for a pattern that turns into `target` after one generation.
+///
+/// Only the cells inside `target` are checked, so the parent is allowed to create cells just
+/// outside of it. This is what makes "no predecessor" mean a real Garden of Eden.
+pub fn find_predecessor(
+    target: &Grid<bool>,
+    rule: &Rule,
+    cancel: &AtomicBool,
+) -> PredecessorResult {
+    let (target_w, target_h) = (target.cols(), target.rows());
+    let (w, h) = (target_w + 2, target_h + 2);
+    let mut parent: Vec<Option<bool>> = vec![None; w * h];
+
+    // Whether some choice of the unassigned cells around target cell (x, y) gives the right state.
+    let feasible = |parent: &[Option<bool>], x: usize, y: usize| -> bool {
+        let wanted = target[(y, x)];
+        let mut alive = 0;
+        let mut unknown = 0;
+
+        for dy in 0..3 {
+            for dx in 0..3 {
+                if dx == 1 && dy == 1 {
+                    continue;
+                }
+                match parent[(y + dy) * w + x + dx] {
+                    Some(true) => alive += 1,
+                    Some(false) => (),
+                    None => unknown += 1,
+                }
+            }
+        }
+
+        let centers: &[bool] = match parent[(y + 1) * w + x + 1] {
+            Some(true) => &[true],
+            Some(false) => &[false],
+            None => &[false, true],
+        };
+
+        centers.iter().any(|&center| {
+            (alive..=alive + unknown).any(|count| {
+                let next = if center {
+                    rule.survive(count)
+                } else {
+                    rule.born(count)
+                };
+                next == wanted
+            })
+        })
+    };
+
+    // Checks every target cell that can see the parent cell at index i.
+    let consistent = |parent: &[Option<bool>], i: usize| -> bool {
+        let (px, py) = (i % w, i / w);
+
+        (py.saturating_sub(2)..=py.min(target_h - 1))
+            .all(|y| (px.saturating_sub(2)..=px.min(target_w - 1)).all(|x| feasible(parent, x, y)))
+    };
+
+    if target_w == 0 || target_h == 0 {
+        return PredecessorResult::Found(Grid::new(h, w));
+    }
+
+    let mut i = 0;
+    let mut steps: usize = 0;
+
+    loop {
+        if i == parent.len() {
+            let tiles = parent.iter().map(|v| v.unwrap_or(false)).collect();
+            return PredecessorResult::Found(Grid::from_vec(tiles, w));
+        }
+
+        steps += 1;
+        if steps.is_multiple_of(4096) && cancel.load(Ordering::Relaxed) {
+            return PredecessorResult::Cancelled;
+        }
+
+        // Try dead first, then alive, then give up on this cell and back up.
+        parent[i] = match parent[i] {
+            None => Some(false),
+            Some(false) => Some(true),
+            Some(true) => {
+                parent[i] = None;
+                if i == 0 {
+                    return PredecessorResult::GardenOfEden;
+                }
+                i -= 1;
+                continue;
+            }
+        };
+
+        if consistent(&parent, i) {
+            i += 1;
+        }
+    }
+}
@@ -7,3 +7,981 @@ fn tiny_strs() {
     assert_ne!(tiny_str!("hi").0, tiny_str!("hello world").0);
     assert_eq!(tiny_str!("hi").to_string(), "hi");
 }
+
+#[test]
+fn canonical_patterns() {
+    let glider = Pattern::new([(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+
+    for transform in Transform::ALL {
+        let moved = glider.transform(transform).translate(7, -3);
+        assert_eq!(moved.canonical(), glider.canonical());
+    }
+
+    assert_ne!(
+        glider.canonical(),
+        Pattern::new([(0, 0), (1, 0), (2, 0)]).canonical()
+    );
+}
+
+#[test]
+fn methuselah_lifespans() {
+    use crate::search::methuselah::measure_lifespan;
+    let rule: Rule = "23/3".into();
+
+    let blinker = Pattern::new([(0, 0), (1, 0), (2, 0)]);
+    let lifespan = measure_lifespan(&blinker, &rule, 100).unwrap();
+    assert_eq!((lifespan.generations, lifespan.final_population), (0, 3));
+
+    let r_pentomino = Pattern::new([(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)]);
+    let lifespan = measure_lifespan(&r_pentomino, &rule, 2000).unwrap();
+    assert_eq!(
+        (lifespan.generations, lifespan.final_population),
+        (1103, 116)
+    );
+}
+
+#[test]
+fn predecessors() {
+    use crate::search::predecessor::{find_predecessor, PredecessorResult};
+    use std::sync::atomic::AtomicBool;
+    let rule: Rule = "23/3".into();
+    let cancel = AtomicBool::new(false);
+
+    let mut target = Grid::new(5, 5);
+    for x in 1..=3 {
+        target[(2, x)] = true;
+    }
+    let PredecessorResult::Found(parent) = find_predecessor(&target, &rule, &cancel) else {
+        panic!("a blinker has a predecessor");
+    };
+    let child = Pattern::from_grid(&parent).step(&rule).translate(-1, -1);
+    for ((y, x), v) in target.indexed_iter() {
+        assert_eq!(child.contains(x as i32, y as i32), *v);
+    }
+
+    // Nothing is ever born or survives under this rule, so a live cell can't have a parent.
+    let mut lonely = Grid::new(3, 3);
+    lonely[(1, 1)] = true;
+    assert!(matches!(
+        find_predecessor(&lonely, &"/".into(), &cancel),
+        PredecessorResult::GardenOfEden
+    ));
+}
+
+#[test]
+fn periodic_search() {
+    use crate::search::periodic::{PeriodicSearch, SearchResult};
+    use std::sync::atomic::AtomicBool;
+    let rule: Rule = "23/3".into();
+    let cancel = AtomicBool::new(false);
+
+    // The only period 2 oscillator that fits in a 3x3 box is the blinker.
+    let mut search = PeriodicSearch::new(&rule, 2, 0, 0, 3, 3);
+    let SearchResult::Found(found) = search.next(&rule, &cancel, |_| ()) else {
+        panic!("should find a blinker");
+    };
+    assert_eq!(
+        found.canonical(),
+        Pattern::new([(0, 0), (1, 0), (2, 0)]).canonical()
+    );
+
+    // The glider, moving one cell diagonally every 4 generations.
+    let mut search = PeriodicSearch::new(&rule, 4, 1, -1, 4, 4);
+    let SearchResult::Found(found) = search.next(&rule, &cancel, |_| ()) else {
+        panic!("should find a glider");
+    };
+    assert_eq!(found.population(), 5);
+}
+
+#[test]
+fn analysis() {
+    let rule: Rule = "23/3".into();
+
+    let block = Pattern::new([(0, 0), (1, 0), (0, 1), (1, 1)]);
+    assert!(matches!(
+        analyze(&block, &rule, 100),
+        Analysis::StillLife { population: 4, .. }
+    ));
+
+    let blinker = Pattern::new([(0, 0), (1, 0), (2, 0)]);
+    let Analysis::Oscillator {
+        period,
+        rotor,
+        stator,
+        heat,
+        ..
+    } = analyze(&blinker, &rule, 100)
+    else {
+        panic!("the blinker is an oscillator");
+    };
+    assert_eq!((period, rotor, stator, heat), (2, 4, 1, 4.0));
+
+    let glider = Pattern::new([(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    let analysis = analyze(&glider, &rule, 100);
+    assert!(matches!(analysis, Analysis::Spaceship { period: 4, .. }));
+    assert!(analysis.to_string().contains("speed: c/4"));
+}
+
+#[test]
+fn symmetric_soups() {
+    let soup = random_soup(9, 9, 0.5, SoupSymmetry::C4, 42);
+    assert_eq!(soup, random_soup(9, 9, 0.5, SoupSymmetry::C4, 42));
+
+    let pattern = Pattern::from_grid(&soup);
+    assert_eq!(
+        pattern.transform(Transform::Rotate90).normalized(),
+        pattern.normalized()
+    );
+
+    let soup = random_soup(6, 4, 0.5, SoupSymmetry::D4, 7);
+    for ((y, x), v) in soup.indexed_iter() {
+        assert_eq!(*v, soup[(y, 5 - x)]);
+        assert_eq!(*v, soup[(3 - y, x)]);
+    }
+
+    let soup = random_soup(100, 100, 0.25, SoupSymmetry::C1, 3);
+    assert_eq!(soup, random_soup(100, 100, 0.25, SoupSymmetry::C1, 3));
+    let alive = soup.iter().filter(|t| **t).count();
+    assert!((2000..3000).contains(&alive));
+}
+
+#[test]
+fn rle() {
+    let text = "#N Glider\n#C A comment.\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n";
+    let file = parse_rle(text).unwrap();
+
+    assert_eq!(file.name.as_deref(), Some("Glider"));
+    assert_eq!(file.comments, vec!["A comment."]);
+    assert_eq!(file.rule, Some("23/3".into()));
+    // The first row in the file is the top row, which is the last row of the grid.
+    assert!(file.tiles[(2, 1)] && file.tiles[(0, 0)] && !file.tiles[(2, 0)]);
+    assert_eq!(Pattern::from_grid(&file.tiles).population(), 5);
+
+    assert_eq!(to_rle(&file), text);
+    let round_trip = parse_rle(&to_rle(&file)).unwrap();
+    assert_eq!(round_trip.tiles, file.tiles);
+
+    // Multi-state letters are all alive, and run counts can span lines.
+    let file = parse_rle("x = 4, y = 2\n2A.pA$\n2\no!").unwrap();
+    assert_eq!(Pattern::from_grid(&file.tiles).population(), 5);
+    assert_eq!((file.tiles.cols(), file.tiles.rows()), (4, 2));
+
+    // Huge patterns are an error, rather than running out of memory.
+    assert!(parse_rle("x = 100000000, y = 100000000\no!").is_err());
+    assert!(parse_rle("99999999999o!").is_err());
+    assert!(parse_rle("18446744073709551615b18446744073709551615o!").is_err());
+    assert!(parse_rle("99999999$o!").is_err());
+
+    // Bounded grids are ignored, and rules we can't run don't stop the file loading.
+    let file = parse_rle("x = 3, y = 1, rule = B36/S23:P30,20\n3o!").unwrap();
+    assert_eq!(file.rule, Some("23/36".into()));
+    assert_eq!(file.tiles.cols(), 3);
+    for rule in ["LifeHistory", "23/3/2"] {
+        let file = parse_rle(&format!("x = 2, y = 1, rule = {}\n2A!", rule)).unwrap();
+        assert_eq!((file.rule, file.warnings.len()), (None, 1));
+        assert_eq!(Pattern::from_grid(&file.tiles).population(), 2);
+    }
+
+    assert_eq!(Rule::parse("B36/S23").unwrap().serialize(), "23/36");
+    assert_eq!(Rule::parse("B3678/S34678").unwrap().to_bs(), "B3678/S34678");
+}
+
+#[test]
+fn pattern_formats() {
+    let glider = parse_rle("x = 3, y = 3, rule = B36/S23\nbo$2bo$3o!").unwrap();
+
+    for format in [
+        Format::Rle,
+        Format::Plaintext,
+        Format::Life105,
+        Format::Life106,
+    ] {
+        let text = format.write(&glider);
+        assert_eq!(Format::detect(&text), format);
+        assert_eq!(format.parse(&text).unwrap().tiles, glider.tiles);
+    }
+
+    let life105 = Format::Life105.write(&glider);
+    assert_eq!(Format::Life105.parse(&life105).unwrap().rule, glider.rule);
+
+    // Blocks in Life 1.05 can be anywhere.
+    let file = parse_life105("#Life 1.05\n#P 0 0\n*\n#P 2 -1\n*").unwrap();
+    assert_eq!((file.tiles.cols(), file.tiles.rows()), (3, 2));
+
+    // ...but not so far apart that the grid holding them would be huge.
+    assert!(parse_life105("#Life 1.05\n#P 0 0\n*\n#P 100000 0\n*").is_err());
+    assert!(parse_life105("#Life 1.05\n#P 9223372036854775807 0\n.*").is_err());
+    assert!(parse_life106("#Life 1.06\n-9223372036854775808 0\n9223372036854775807 0").is_err());
+
+    // Rules we can't run are a warning, like in RLE files.
+    let file = parse_life105("#Life 1.05\n#R 23/3/3\n#P 0 0\n*").unwrap();
+    assert_eq!((file.rule, file.warnings.len()), (None, 1));
+}
+
+#[test]
+fn macrocell() {
+    let text = "[M2] (golly 2.0)\n#R B3/S23\n.*$..*$***$\n4 1 0 0 1\n";
+    let file = parse_macrocell(text).unwrap();
+
+    // Two gliders, 8 cells apart diagonally.
+    assert_eq!(Pattern::from_grid(&file.tiles).population(), 10);
+    assert_eq!((file.tiles.cols(), file.tiles.rows()), (11, 11));
+
+    // The same glider twice is only stored once.
+    let written = to_macrocell(&file);
+    assert_eq!(written.lines().filter(|l| l.ends_with('$')).count(), 1);
+    assert_eq!(parse_macrocell(&written).unwrap().tiles, file.tiles);
+
+    // Rules we can't run are a warning, like in RLE files.
+    let file = parse_macrocell("[M2]\n#R 23/3/3\n.*$..*$***$\n4 1 0 0 0\n").unwrap();
+    assert_eq!((file.rule, file.warnings.len()), (None, 1));
+    assert_eq!(Pattern::from_grid(&file.tiles).population(), 5);
+
+    // Children have to be one level down.
+    assert!(MacroCell::parse("[M2]\n*$\n4 1 0 0 0\n6 2 0 0 0\n").is_err());
+    // Nodes can be huge without being expanded, until they go on the board.
+    let huge: String = std::iter::once("[M2]\n*$\n".to_string())
+        .chain((4..=40).map(|level| format!("{} {} 0 0 {}\n", level, level - 3, level - 3)))
+        .collect();
+    let macrocell = MacroCell::parse(&huge).unwrap();
+    assert!(macrocell.to_grid().is_err());
+    assert_eq!(
+        MacroCell::parse(&macrocell.to_text()).unwrap().to_text(),
+        macrocell.to_text()
+    );
+    // Too deep for the cell positions to fit in an i64.
+    let too_deep: String = std::iter::once("[M2]\n*$\n".to_string())
+        .chain((4..=70).map(|level| format!("{} {} 0 0 {}\n", level, level - 3, level - 3)))
+        .collect();
+    assert!(MacroCell::parse(&too_deep).is_err());
+}
+
+#[test]
+fn savestate_versions() {
+    let glider = parse_rle("bo$2bo$3o!").unwrap().tiles;
+    let board = Board { tiles: glider };
+
+    // Files from before the header are still readable.
+    let old = SaveState::from_bytes(&bincode::serialize(&board).unwrap()).unwrap();
+    assert_eq!(old.board.tiles, board.tiles);
+    assert_eq!(old.metadata.generation, 0);
+
+    // Cells that don't fill whole rows are an error, not a crash. The width comes first.
+    for width in [0u64, 4] {
+        let mut bytes = bincode::serialize(&board).unwrap();
+        bytes[..8].copy_from_slice(&width.to_le_bytes());
+        assert!(matches!(
+            SaveState::from_bytes(&bytes),
+            Err(SaveError::Corrupt(_))
+        ));
+    }
+    let empty = bincode::serialize(&Board::new(5, 5)).unwrap();
+    assert!(SaveState::from_bytes(&empty)
+        .unwrap()
+        .board
+        .tiles
+        .is_empty());
+
+    let mut savestate = old.clone();
+    savestate.metadata.name = "Glider".to_string();
+    savestate.metadata.rule = Rule::parse("B36/S23").unwrap();
+    savestate.metadata.generation = 42;
+    savestate.metadata.offset = (3, 4);
+    savestate.metadata.board_size = Some((100, 80));
+
+    let bytes = savestate.to_bytes();
+    assert!(bytes.starts_with(b"GOLSTATE"));
+    let loaded = SaveState::from_bytes(&bytes).unwrap();
+    assert_eq!(loaded.board.tiles, board.tiles);
+    assert_eq!(loaded.metadata.name, "Glider");
+    assert_eq!(loaded.metadata.rule, savestate.metadata.rule);
+    assert_eq!(
+        (loaded.metadata.generation, loaded.metadata.offset),
+        (42, (3, 4))
+    );
+    assert_eq!(loaded.metadata.board_size, Some((100, 80)));
+
+    // Version 1 didn't have the board size.
+    let mut v1 = b"GOLSTATE".to_vec();
+    v1.extend(1u16.to_le_bytes());
+    v1.extend(
+        bincode::serialize(&(
+            "Glider",
+            "",
+            "23/3",
+            7u64,
+            (3i64, 4i64),
+            (0f32, 0f32),
+            "",
+            &board,
+        ))
+        .unwrap(),
+    );
+    let migrated = SaveState::from_bytes(&v1).unwrap();
+    assert_eq!(migrated.metadata.name, "Glider");
+    assert_eq!(migrated.metadata.generation, 7);
+    assert_eq!(migrated.metadata.board_size, None);
+    assert_eq!(migrated.board.tiles, board.tiles);
+
+    // Newer versions and broken files are refused.
+    let mut newer = bytes.clone();
+    newer[8] = 0xff;
+    assert!(matches!(
+        SaveState::from_bytes(&newer),
+        Err(SaveError::UnsupportedVersion(_))
+    ));
+    assert!(matches!(
+        SaveState::from_bytes(&bytes[..bytes.len() - 3]),
+        Err(SaveError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn thumbnails() {
+    let mut tiles = Grid::new(50, 100);
+    tiles[(0, 0)] = true;
+    tiles[(49, 99)] = true;
+
+    let small = thumbnail(&tiles, 10);
+    assert_eq!((small.cols(), small.rows()), (10, 5));
+    assert!(small[(0, 0)] && small[(4, 9)]);
+    assert_eq!(small.iter().filter(|alive| **alive).count(), 2);
+
+    // Small boards aren't scaled up.
+    assert_eq!(thumbnail(&Grid::new(3, 4), 10).cols(), 4);
+}
+
+#[test]
+fn browser_search() {
+    // The L that opens the browser isn't typed into the search.
+    let mut browser = SavestateBrowser::default();
+    browser.skip_next_char();
+    for c in "lglider".chars() {
+        browser.type_char(c);
+    }
+    assert_eq!(browser.search(), "glider");
+
+    browser.type_char('\u{8}');
+    assert_eq!(browser.search(), "glider");
+}
+
+#[test]
+fn unique_names() {
+    let id = timestamp_id();
+    assert_eq!(id.len(), "2024-01-02_03-04-05".len());
+    assert!(!id.contains([':', ' ']));
+
+    let dir = std::env::temp_dir().join("gol-unique-names");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let dir = dir.to_string_lossy().to_string();
+
+    assert_eq!(unique_name(&dir, "save", "gol"), "save");
+    std::fs::write(format!("{}/save.gol", dir), "").unwrap();
+    assert_eq!(unique_name(&dir, "save", "gol"), "save-2");
+    std::fs::write(format!("{}/save-2.gol", dir), "").unwrap();
+    assert_eq!(unique_name(&dir, "save", "gol"), "save-3");
+    assert_eq!(unique_name(&dir, "save", "rle"), "save");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn autosaves() {
+    let dir = std::env::temp_dir().join(format!("gol-autosaves-{}", std::process::id()));
+    let dir = dir.to_str().unwrap();
+    let board = Board::new(4, 4);
+    let mut savestate = SaveState::from_bytes(&bincode::serialize(&board).unwrap()).unwrap();
+
+    let names: Vec<String> = (0..5)
+        .map(|_| write_autosave(dir, &mut savestate, 3).unwrap())
+        .collect();
+    assert_eq!(list_autosaves(dir), names[2..]);
+    // Keeping none still keeps the one just written.
+    let name = write_autosave(dir, &mut savestate, 0).unwrap();
+    assert_eq!(list_autosaves(dir), [name]);
+
+    // -10 comes after -9, and anything from a later second comes after both.
+    std::fs::remove_dir_all(dir).unwrap();
+    std::fs::create_dir_all(dir).unwrap();
+    let names = [
+        "2026-01-01_00-00-00",
+        "2026-01-01_00-00-00-9",
+        "2026-01-01_00-00-00-10",
+        "2026-01-01_00-00-01",
+    ];
+    for name in names.iter().rev() {
+        std::fs::write(format!("{}/{}.gol", dir, name), savestate.to_bytes()).unwrap();
+    }
+    assert_eq!(list_autosaves(dir), names);
+
+    // A second instance doesn't mistake the first one's lock for a crash.
+    let lock = format!("{}/session.lock", dir);
+    let session = start_session(&lock).unwrap();
+    assert!(!session.crashed);
+    assert!(start_session(&lock).is_none());
+    // Closing without ending the session is a crash.
+    drop(session);
+    let session = start_session(&lock).unwrap();
+    assert!(session.crashed);
+    session.end();
+    assert!(!start_session(&lock).unwrap().crashed);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn savestate_layouts() {
+    let mut board = Board::new(60, 40);
+    for (x, y) in [(10, 5), (11, 5), (50, 30)] {
+        board.set(x, y, true);
+    }
+    let mut savestate = SaveState::from_bytes(&bincode::serialize(&board).unwrap()).unwrap();
+    savestate.metadata.offset = (10, 5);
+    savestate.metadata.board_size = Some((60, 40));
+    savestate.metadata.camera_offset = (12., -8.);
+
+    let loaded = SaveState::from_bytes(&savestate.to_bytes()).unwrap();
+    assert_eq!(loaded.board.wh(), (41, 26));
+    assert_eq!(loaded.metadata.camera_offset, (12., -8.));
+    let tiles = loaded.tiles();
+    assert_eq!(tiles, board.tiles);
+
+    // The saved board is bigger than the current one, so it's used as is.
+    let restored = fit_board(&tiles, (30, 30));
+    assert_eq!(restored.tiles, board.tiles);
+    // A bigger board keeps the layout, just moved into the middle.
+    let restored = fit_board(&tiles, (80, 60));
+    assert_eq!(restored.wh(), (80, 60));
+    let cells = Pattern::from_grid(&restored.tiles);
+    assert_eq!(cells, Pattern::new([(20, 15), (21, 15), (60, 40)]));
+
+    // Without the layout, the pattern comes back cropped.
+    savestate.metadata.board_size = None;
+    let cropped = SaveState::from_bytes(&savestate.to_bytes())
+        .unwrap()
+        .tiles();
+    assert_eq!((cropped.cols(), cropped.rows()), (41, 26));
+}
+
+#[test]
+fn savestate_encodings() {
+    let sparse = {
+        let mut tiles = Grid::new(300, 400);
+        tiles[(10, 20)] = true;
+        tiles[(250, 399)] = true;
+        tiles
+    };
+    let solid = {
+        let mut tiles = Grid::new(300, 400);
+        tiles.fill(true);
+        tiles[(0, 0)] = false;
+        tiles
+    };
+    let soup = random_soup(300, 400, 0.5, SoupSymmetry::C1, 1);
+
+    assert_eq!(Encoding::choose(&sparse), Encoding::Cells);
+    assert_eq!(Encoding::choose(&solid), Encoding::Runs);
+    assert_eq!(Encoding::choose(&soup), Encoding::Bits);
+
+    for tiles in [&sparse, &solid, &soup] {
+        for encoding in [Encoding::Bits, Encoding::Runs, Encoding::Cells] {
+            let bytes = encoding.encode(tiles);
+            let decoded = encoding.decode(&bytes, tiles.cols(), tiles.rows());
+            assert_eq!(decoded.as_ref(), Ok(tiles));
+        }
+    }
+    assert!(Encoding::Cells.encode(&sparse).len() < 10);
+    assert!(Encoding::Runs.decode(&[200, 1], 10, 10).is_err());
+
+    // Version 2 stored the board as a bitvec, with no encoding byte.
+    let board = Board { tiles: sparse };
+    let savestate = SaveState::from_bytes(&bincode::serialize(&board).unwrap()).unwrap();
+    let mut v2 = b"GOLSTATE".to_vec();
+    v2.extend(2u16.to_le_bytes());
+    v2.extend(bincode::serialize(&(&savestate.metadata, &board)).unwrap());
+    let mut cropped = board.clone();
+    cropped.crop();
+    assert_eq!(
+        SaveState::from_bytes(&v2).unwrap().board.tiles,
+        cropped.tiles
+    );
+
+    // A header claiming a huge board is an error, rather than running out of memory.
+    for encoding in [Encoding::Bits, Encoding::Runs, Encoding::Cells] {
+        assert!(encoding.decode(&[1], 1 << 20, 1 << 20).is_err());
+        let mut huge = b"GOLSTATE".to_vec();
+        huge.extend(SAVESTATE_VERSION.to_le_bytes());
+        huge.push(encoding.to_byte());
+        huge.extend(bincode::serialize(&(&savestate.metadata, 1u64 << 40, 1u64 << 40)).unwrap());
+        huge.push(1);
+        assert!(matches!(
+            SaveState::from_bytes(&huge),
+            Err(SaveError::Corrupt(_))
+        ));
+    }
+
+    let bytes = savestate.to_bytes();
+    assert_eq!(bytes[10], Encoding::Cells.to_byte());
+    assert_eq!(
+        SaveState::from_bytes(&bytes).unwrap().board.tiles,
+        cropped.tiles
+    );
+}
+
+#[test]
+fn image_import() {
+    use nannou::image::{GrayImage, Luma};
+
+    // A left to right gradient, black to white.
+    let gradient = GrayImage::from_fn(64, 16, |x, _| Luma([(x * 4) as u8]));
+    let path = std::env::temp_dir().join("gol-image-import.png");
+    gradient.save(&path).unwrap();
+
+    let mut options = ImageImport {
+        dither: Dither::Threshold,
+        threshold: 128,
+        invert: false,
+        fit: None,
+    };
+    let population = |tiles: &Grid<bool>| tiles.iter().filter(|alive| **alive).count();
+
+    let tiles = import_image(&path, &options).unwrap();
+    assert_eq!((tiles.cols(), tiles.rows()), (64, 16));
+    assert_eq!(population(&tiles), 32 * 16);
+    assert!(tiles[(0, 0)] && !tiles[(0, 63)]);
+
+    // Dithering keeps the overall brightness, and the dark side stays denser.
+    for dither in [Dither::FloydSteinberg, Dither::Ordered] {
+        options.dither = dither;
+        let tiles = import_image(&path, &options).unwrap();
+        assert!(population(&tiles).abs_diff(32 * 16) < 32);
+        let left: usize = (0..16).map(|y| tiles[(y, 8)] as usize).sum();
+        let right: usize = (0..16).map(|y| tiles[(y, 56)] as usize).sum();
+        assert!(left > right);
+    }
+
+    options.fit = Some((32, 32));
+    let tiles = import_image(&path, &options).unwrap();
+    assert_eq!((tiles.cols(), tiles.rows()), (32, 8));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn rasterize_cells() {
+    // The glider, top row first in the image.
+    let glider = parse_rle("bo$2bo$3o!").unwrap().tiles;
+    let cell = CONFIG.cell_color.to_rgb8();
+    let background = CONFIG.background_color.to_rgb8();
+
+    let image = rasterize(&glider, &RasterOptions::new(2, false)).unwrap();
+    assert_eq!(image.dimensions(), (6, 6));
+    assert_eq!(image.get_pixel(0, 0).0, background);
+    assert_eq!(image.get_pixel(3, 1).0, cell);
+    assert_eq!(image.get_pixel(5, 3).0, cell);
+    assert_eq!(image.get_pixel(0, 5).0, cell);
+
+    // Grid lines go around every cell.
+    let image = rasterize(&glider, &RasterOptions::new(4, true)).unwrap();
+    assert_eq!(image.dimensions(), (13, 13));
+    assert_eq!(image.get_pixel(4, 2).0, CONFIG.grid_color.to_rgb8());
+    assert_eq!(image.get_pixel(6, 2).0, cell);
+    assert_eq!(image.get_pixel(12, 12).0, CONFIG.grid_color.to_rgb8());
+
+    // Shrunk, each pixel is a 2x2 square of cells from the bottom left, alive if any of them are.
+    let shrunk = RasterOptions::new(1, true).cells_per_pixel(2);
+    let image = rasterize(&glider, &shrunk).unwrap();
+    assert_eq!(image.dimensions(), (2, 2));
+    assert_eq!(image.get_pixel(0, 0).0, cell);
+    assert_eq!(image.get_pixel(1, 0).0, background);
+    assert_eq!(image.get_pixel(0, 1).0, cell);
+    assert_eq!(image.get_pixel(1, 1).0, cell);
+
+    assert!(rasterize(&glider, &RasterOptions::new(u32::MAX, false)).is_err());
+}
+
+#[test]
+fn recordings() {
+    use nannou::image::GenericImageView;
+    let glider = parse_rle("bo$2bo$3o!").unwrap().tiles;
+
+    // Every other generation, cropped to the top two rows.
+    let mut recorder = Recorder::new(
+        RasterOptions::new(1, false),
+        2,
+        Some(Region(0, 1, 3, 2)),
+        50,
+    );
+    for _ in 0..5 {
+        assert!(recorder.capture(&glider));
+    }
+    assert_eq!(recorder.frame_count(), 3);
+
+    let dir = std::env::temp_dir().join(format!("gol-recording-{}", std::process::id()));
+    recorder.save(dir.join("run.gif")).unwrap();
+    let gif = nannou::image::open(dir.join("run.gif")).unwrap();
+    assert_eq!(gif.dimensions(), (3, 2));
+
+    recorder.save(dir.join("frames")).unwrap();
+    let frame = nannou::image::open(dir.join("frames/frame_00002.png")).unwrap();
+    assert_eq!(frame.dimensions(), (3, 2));
+    assert!(!dir.join("frames/frame_00003.png").exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn svg_export() {
+    let block = parse_rle("3o$3o$3o!").unwrap().tiles;
+    assert_eq!(
+        merge_rects(&block),
+        vec![CellRect {
+            x: 0,
+            y: 0,
+            width: 3,
+            height: 3
+        }]
+    );
+
+    // Runs only merge with the same run in the next row.
+    let glider = parse_rle("bo$2bo$3o!").unwrap().tiles;
+    let rects = merge_rects(&glider);
+    assert_eq!(rects.len(), 3);
+    assert_eq!(rects.iter().map(|r| r.width * r.height).sum::<usize>(), 5);
+
+    let svg = to_svg(&glider, &SvgOptions::new(10, true, true).origin(20, 5));
+    assert_eq!(svg.matches("<rect").count(), 4);
+    assert!(svg.contains(&CONFIG.cell_color.to_hex()));
+    assert!(svg.contains(">20</text>") && svg.contains(">5</text>"));
+    assert!(svg.contains("<path"));
+}
+
+#[test]
+fn pattern_library() {
+    let library = bundled_patterns(16);
+    let find = |name: &str| library.iter().find(|p| p.name == name).unwrap();
+
+    for category in [
+        "Still lifes",
+        "Oscillators",
+        "Spaceships",
+        "Guns",
+        "Puffers",
+        "Methuselahs",
+    ] {
+        assert!(library.iter().any(|p| p.category == category));
+    }
+    assert!(library.iter().all(|p| p.file.rule.is_some()));
+
+    // The patterns do what they say.
+    let rule: Rule = "23/3".into();
+    let pulsar = Pattern::from_grid(&find("Pulsar").file.tiles);
+    let after = (0..3).fold(pulsar.clone(), |p, _| p.step(&rule));
+    assert_eq!(after.normalized(), pulsar.normalized());
+
+    let gun = Pattern::from_grid(&find("Gosper glider gun").file.tiles);
+    let after = (0..120).fold(gun.clone(), |p, _| p.step(&rule));
+    assert_eq!(after.population(), gun.population() + 4 * 5);
+}
+
+#[test]
+fn grid_transforms() {
+    // An L, 2 wide and 3 tall, bottom row first.
+    let l = Grid::from_vec(vec![true, true, true, false, true, false], 2);
+
+    for transform in Transform::ALL {
+        let grid = transform.apply_grid(&l);
+        let pattern = Pattern::from_grid(&grid);
+        assert_eq!(pattern.population(), 4);
+        assert_eq!(
+            pattern.normalized(),
+            Pattern::from_grid(&l).transform(transform).normalized()
+        );
+    }
+
+    // A quarter turn counter clockwise puts the long side along the bottom.
+    let turned = Transform::Rotate90.apply_grid(&l);
+    assert_eq!((turned.cols(), turned.rows()), (3, 2));
+    assert_eq!(
+        turned.iter().copied().collect::<Vec<_>>(),
+        vec![true, true, true, false, false, true]
+    );
+}
+
+#[test]
+fn paste_modes() {
+    let paste = Grid::from_vec(vec![true, false, true, false], 2);
+    let modes = [
+        (PasteMode::Copy, [true, false, true, false]),
+        (PasteMode::Or, [true, true, true, false]),
+        (PasteMode::And, [true, false, false, false]),
+        (PasteMode::Xor, [false, true, true, false]),
+    ];
+
+    for (mode, expected) in modes {
+        let mut board = Board::new(3, 3);
+        board.set(0, 0, true);
+        board.set(1, 0, true);
+        paste_into(&mut board, &paste, (0, 0), mode);
+
+        let result = [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(x, y)| board.get_or_empty(x, y));
+        assert_eq!(result, expected, "{}", mode);
+    }
+
+    // Only the part on the board is pasted.
+    let mut board = Board::new(3, 3);
+    paste_into(&mut board, &paste, (-1, 2), PasteMode::Or);
+    assert_eq!(board.tiles.iter().filter(|t| **t).count(), 0);
+    paste_into(&mut board, &paste, (2, -1), PasteMode::Or);
+    assert!(board.get_or_empty(2, 0));
+    assert_eq!(board.tiles.iter().filter(|t| **t).count(), 1);
+}
+
+#[test]
+fn selection_transforms() {
+    let live = |board: &Board| {
+        let mut cells: Vec<(usize, usize)> = board
+            .tiles
+            .indexed_iter()
+            .filter(|(_, tile)| **tile)
+            .map(|((y, x), _)| (x, y))
+            .collect();
+        cells.sort();
+        cells
+    };
+
+    // An L in a 2x3 selection, with its corner at (5, 5).
+    let mut board = Board::new(12, 12);
+    for (x, y) in [(5, 5), (6, 5), (5, 6), (5, 7)] {
+        board.set(x, y, true);
+    }
+    let original = live(&board);
+    let selection = Selection::rect(VecU2::new(5, 5), VecU2::new(6, 7));
+
+    // Flips and half turns stay put, and undo themselves.
+    for transform in [Transform::FlipX, Transform::FlipY, Transform::Rotate180] {
+        let flipped = selection.transform_board(&mut board, transform).unwrap();
+        assert_eq!(flipped.start.as_tuple(), (5, 5));
+        assert_eq!(flipped.end.as_tuple(), (6, 7));
+        assert_eq!(live(&board).len(), 4);
+        flipped.transform_board(&mut board, transform).unwrap();
+        assert_eq!(live(&board), original);
+    }
+
+    // Quarter turns go back to the same place.
+    let turned = selection
+        .transform_board(&mut board, Transform::Rotate90)
+        .unwrap();
+    assert_eq!(turned.wh(), (2, 1));
+    let back = turned
+        .transform_board(&mut board, Transform::Rotate270)
+        .unwrap();
+    assert_eq!(back.start.as_tuple(), (5, 5));
+    assert_eq!(live(&board), original);
+
+    // Near the origin, the result is pushed back onto the board instead of wrapping around.
+    let mut board = Board::new(8, 12);
+    for y in 0..8 {
+        board.set(0, y, true);
+    }
+    let column = Selection::rect(VecU2::new(0, 0), VecU2::new(0, 7));
+    let row = column
+        .transform_board(&mut board, Transform::Transpose)
+        .unwrap();
+    assert_eq!(row.start.as_tuple(), (0, 3));
+    assert_eq!(row.end.as_tuple(), (7, 3));
+    assert_eq!(live(&board), (0..8).map(|x| (x, 3)).collect::<Vec<_>>());
+
+    // If it doesn't fit at all, nothing happens.
+    let mut board = Board::new(4, 12);
+    for y in 0..8 {
+        board.set(0, y, true);
+    }
+    let column_cells = live(&board);
+    assert!(column
+        .transform_board(&mut board, Transform::Transpose)
+        .is_err());
+    assert_eq!(live(&board), column_cells);
+}
+
+#[test]
+fn clipboard_history() {
+    let glider = parse_rle("bo$2bo$3o!").unwrap().tiles;
+    let block = parse_rle("2o$2o!").unwrap().tiles;
+    let blinker = parse_rle("3o!").unwrap().tiles;
+
+    let mut history = ClipboardHistory::default();
+    history.push(glider.clone(), 2);
+    history.push(block.clone(), 2);
+    assert_eq!(history.get(1), Some(&block));
+    assert_eq!(history.get(2), Some(&glider));
+    assert_eq!(history.get(0), None);
+
+    // Copying something again moves it to the front, and old clips fall off the end.
+    history.push(glider.clone(), 2);
+    assert_eq!(history.len(), 2);
+    assert_eq!(history.get(1), Some(&glider));
+    history.push(blinker.clone(), 2);
+    assert_eq!(history.iter().collect::<Vec<_>>(), vec![&blinker, &glider]);
+
+    // Named clips are saved as RLE, and never overwritten.
+    let dir = std::env::temp_dir().join(format!("gol-clips-{}", std::process::id()));
+    let dir = dir.to_str().unwrap();
+    save_clip(dir, "glider", &glider).unwrap();
+    assert!(save_clip(dir, "glider", &block).is_err());
+    assert!(save_clip(dir, "../escape", &block).is_err());
+
+    let file = read_pattern(format!("{}/glider.rle", dir)).unwrap();
+    assert_eq!(file.tiles, glider);
+    assert_eq!(file.name.as_deref(), Some("glider"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn selection_masks() {
+    let mut board = Board::new(20, 20);
+    // Two gliders, 3 cells apart, and a lone cell.
+    for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+        board.set(x + 2, y + 2, true);
+        board.set(x + 8, y + 2, true);
+    }
+    board.set(15, 15, true);
+
+    // The wand picks one glider, or both once the gap is big enough to cross.
+    let one = clusters(&board, [(4, 4)], Adjacency::Moore, 0);
+    assert_eq!(one.len(), 5);
+    assert_eq!(clusters(&board, [(4, 4)], Adjacency::Moore, 3).len(), 10);
+    assert_eq!(
+        clusters(&board, [(3, 2)], Adjacency::VonNeumann, 0).len(),
+        1
+    );
+    assert!(clusters(&board, [(0, 0)], Adjacency::Moore, 5).is_empty());
+
+    let glider = Selection::from_cells(one).unwrap();
+    assert_eq!((glider.min(), glider.wh()), ((2, 2), (2, 2)));
+    assert!(glider.contains(3, 2) && !glider.contains(2, 2));
+
+    // Copying, clearing and moving only touch the mask.
+    let everything = Selection::rect(VecU2::new(0, 0), VecU2::new(19, 19));
+    let cut = SelectionOp::Subtract
+        .combine(Some(&everything), Some(glider.clone()))
+        .unwrap();
+    assert_eq!(cut.cells().len(), 400 - 5);
+    cut.clear_board(&mut board);
+    assert_eq!(board.tiles.iter().filter(|t| **t).count(), 5);
+    assert_eq!(
+        glider.board_tiles(&board),
+        parse_rle("3o$2bo$bo!").unwrap().tiles
+    );
+
+    let flipped = glider
+        .transform_board(&mut board, Transform::FlipY)
+        .unwrap();
+    assert_eq!(
+        flipped.board_tiles(&board),
+        parse_rle("bo$2bo$3o!").unwrap().tiles
+    );
+    assert_eq!(flipped.cells().len(), 5);
+
+    let added = SelectionOp::Add.combine(Some(&flipped), Selection::from_cells([(10, 10)]));
+    assert_eq!(added.unwrap().cells().len(), 6);
+
+    // The outline is worked out up front, with straight runs joined into one line.
+    let corner = Selection::from_cells([(4, 4), (5, 4), (4, 5)]).unwrap();
+    let mut edges = corner.edges().to_vec();
+    edges.sort();
+    assert_eq!(
+        edges,
+        vec![
+            [(0, 0), (0, 2)],
+            [(0, 0), (2, 0)],
+            [(0, 2), (1, 2)],
+            [(1, 1), (1, 2)],
+            [(1, 1), (2, 1)],
+            [(2, 0), (2, 1)],
+        ]
+    );
+    let square = Selection::from_cells([(4, 4), (5, 4), (4, 5), (5, 5)]).unwrap();
+    assert!(square.edges().is_empty());
+    assert_eq!(
+        SelectionOp::Subtract
+            .combine(Some(&square), Some(corner))
+            .unwrap()
+            .cells(),
+        vec![(5, 5)]
+    );
+}
+
+#[test]
+fn lasso_selections() {
+    // A diamond drawn around (5, 5).
+    let selection = lasso(&[(5, 2), (8, 5), (5, 8), (2, 5)]).unwrap();
+    assert!(selection.contains(5, 5) && selection.contains(4, 6));
+    assert!(selection.contains(8, 5));
+    assert!(!selection.contains(2, 2) && !selection.contains(8, 8));
+    assert_eq!(selection.wh(), (6, 6));
+
+    // Dragging along the edges of a square selects all of it.
+    let mut square: Vec<(usize, usize)> = (2..=9).map(|x| (x, 2)).collect();
+    square.extend((3..=9).map(|y| (9, y)));
+    square.extend((2..=8).rev().map(|x| (x, 9)));
+    square.extend((3..=8).rev().map(|y| (2, y)));
+    let selection = lasso(&square).unwrap();
+    assert!(selection.edges().is_empty() && selection.wh() == (7, 7));
+    let selection = lasso(&[(2, 2), (9, 2), (9, 9), (2, 9)]).unwrap();
+    assert!(selection.contains(2, 5) && selection.contains(8, 8));
+}
+
+#[test]
+fn selection_operations() {
+    let rule = Rule::parse("B3/S23").unwrap();
+    let alive = |board: &Board| board.tiles.iter().filter(|t| **t).count();
+
+    let mut board = Board::new(12, 12);
+    let selection = Selection::rect(VecU2::new(2, 2), VecU2::new(5, 4));
+    board.set(2, 2, true);
+    selection.invert_board(&mut board);
+    assert_eq!(alive(&board), 4 * 3 - 1);
+    assert!(!board.get_or_empty(2, 2) && board.get_or_empty(5, 4));
+
+    // The clipboard repeats from the bottom left corner of the selection.
+    let mut board = Board::new(12, 12);
+    let checker = parse_rle("bo$ob!").unwrap().tiles;
+    selection.fill_board(&mut board, &checker);
+    assert_eq!(alive(&board), 6);
+    assert_eq!(
+        selection.board_tiles(&board),
+        parse_rle("obob$bobo$obob!").unwrap().tiles
+    );
+
+    // A blinker in the selection keeps blinking, and the cells outside it don't take part.
+    let mut board = Board::new(12, 12);
+    for x in 3..6 {
+        board.set(x, 6, true);
+        board.set(x, 8, true);
+    }
+    let blinker = Selection::rect(VecU2::new(2, 4), VecU2::new(6, 7));
+    blinker.evolve_board(&mut board, 1, &rule);
+    assert!((5..8).all(|y| board.get_or_empty(4, y)));
+    assert!(!board.get_or_empty(3, 6) && !board.get_or_empty(5, 6));
+    assert!((3..6).all(|x| board.get_or_empty(x, 8)));
+    assert_eq!(alive(&board), 6);
+
+    // A glider that runs into the edge of the selection gets clipped.
+    let mut board = Board::new(12, 12);
+    for (x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)] {
+        board.set(x, y, true);
+    }
+    let corner = Selection::rect(VecU2::new(0, 0), VecU2::new(2, 2));
+    let mut free = board.clone();
+    corner.evolve_board(&mut board, 4, &rule);
+    for _ in 0..4 {
+        free.advance();
+    }
+    assert!(alive(&board) < alive(&free));
+    assert!(board
+        .tiles
+        .indexed_iter()
+        .all(|((y, x), t)| !t || corner.contains(x, y)));
+}
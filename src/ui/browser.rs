@@ -0,0 +1,262 @@
+//! The savestate browser, a window listing the saved boards.
+//!
+//! Typing filters the list by name. The arrow keys pick a savestate, Enter loads it, F2 renames it
+//! and Delete (pressed twice) deletes it. Escape closes the browser instead of the game while it's
+//! open.
+
+use crate::prelude::*;
+
+/// How many savestates fit in the window at once.
+const VISIBLE_ROWS: usize = 8;
+/// Thumbnails are at most this many cells across.
+const THUMBNAIL_SIZE: usize = 48;
+
+#[derive(Clone, Debug, Default)]
+pub struct SavestateBrowser {
+    entries: Vec<SavestateEntry>,
+    search: String,
+    /// Index into the filtered list.
+    selected: usize,
+    /// The new name, while renaming the selected savestate.
+    rename: Option<String>,
+    /// Set after Delete is pressed once, so a second press deletes.
+    confirm_delete: bool,
+    /// Set when the browser's opened, since the L that opened it gets typed after the key press.
+    skip_next_char: bool,
+}
+
+impl SavestateBrowser {
+    pub fn new() -> Self {
+        Self {
+            entries: list_savestates(THUMBNAIL_SIZE),
+            ..Default::default()
+        }
+    }
+    /// The savestates whose names match the search.
+    fn filtered(&self) -> Vec<&SavestateEntry> {
+        let search = self.search.to_lowercase();
+
+        self.entries
+            .iter()
+            .filter(|entry| entry.name.to_lowercase().contains(&search))
+            .collect()
+    }
+    fn selected_name(&self) -> Option<String> {
+        self.filtered()
+            .get(self.selected)
+            .map(|entry| entry.name.clone())
+    }
+    /// Reloads the list, after something's been renamed or deleted.
+    fn refresh(&mut self) {
+        self.entries = list_savestates(THUMBNAIL_SIZE);
+        self.selected = self.selected.min(self.filtered().len().saturating_sub(1));
+    }
+    pub fn search(&self) -> &str {
+        &self.search
+    }
+    /// Ignores the next typed character.
+    pub fn skip_next_char(&mut self) {
+        self.skip_next_char = true;
+    }
+    /// Adds a typed character to the search, or to the new name when renaming.
+    pub fn type_char(&mut self, c: char) {
+        if c.is_control() || std::mem::take(&mut self.skip_next_char) {
+            return;
+        }
+
+        self.confirm_delete = false;
+        match &mut self.rename {
+            Some(name) => name.push(c),
+            None => {
+                self.search.push(c);
+                self.selected = 0;
+            }
+        }
+    }
+    pub fn render(&self, draw: &Draw, cache: &Cache, model: &Model) {
+        let filtered = self.filtered();
+        let first = self
+            .selected
+            .saturating_sub(VISIBLE_ROWS - 1)
+            .min(filtered.len().saturating_sub(VISIBLE_ROWS));
+        let rows = &filtered[first..filtered.len().min(first + VISIBLE_ROWS)];
+
+        let mut text = format!(
+            "Savestates ({})    Search: {}\n",
+            filtered.len(),
+            self.search
+        );
+        for (i, entry) in rows.iter().enumerate() {
+            let selected = first + i == self.selected;
+            let name = match &self.rename {
+                Some(name) if selected => format!("{}_", name),
+                _ => entry.name.clone(),
+            };
+
+            text += &format!(
+                "{} {}\n    {}x{}, {} cells, {}\n",
+                if selected { ">" } else { " " },
+                name,
+                entry.size.0,
+                entry.size.1,
+                entry.population,
+                entry.date
+            );
+        }
+        if filtered.is_empty() {
+            text += "No savestates found.\n";
+        }
+
+        let footer = if self.confirm_delete {
+            "Press Delete again to delete the savestate."
+        } else if self.rename.is_some() {
+            "Enter: Rename. Esc: Cancel."
+        } else {
+            "Enter: Load. F2: Rename. Del: Delete. Esc: Close."
+        };
+        // The header, two lines per row, a gap and the footer.
+        let lines = VISIBLE_ROWS * 2 + 3;
+        text += &"\n".repeat(lines - text.lines().count() - 1);
+        text += footer;
+
+        let width = (cache.window_size.0 / 2.).min(800.);
+        let line_height = 1.25;
+        let line = CONFIG.font_size as f32 * line_height;
+
+        UiWindow::new()
+            .text(text)
+            .width(width)
+            .lines(lines)
+            .line_height(line_height)
+            .open(true)
+            .build()
+            .render(draw, cache, model);
+
+        // The thumbnails go on the right of each row, as tall as its two lines.
+        let size = line * 2. * 0.9;
+        let top = lines as f32 * line / 2.;
+        for (i, entry) in rows.iter().enumerate() {
+            let x = width / 2. - size / 2.;
+            let y = top - (i as f32 * 2. + 2.) * line;
+            draw_thumbnail(draw, &entry.thumbnail, x, y, size);
+        }
+    }
+}
+
+/// Draws a thumbnail, fitted into a size x size square centered on (x, y).
+pub fn draw_thumbnail(draw: &Draw, thumbnail: &Grid<bool>, x: f32, y: f32, size: f32) {
+    draw.rect()
+        .x_y(x, y)
+        .w_h(size, size)
+        .color(CONFIG.background_color.to_srgb());
+
+    let (w, h) = (thumbnail.cols(), thumbnail.rows());
+    let cell = size / w.max(h).max(1) as f32;
+    let (left, bottom) = (x - w as f32 * cell / 2., y - h as f32 * cell / 2.);
+
+    for ((row, col), alive) in thumbnail.indexed_iter() {
+        if *alive {
+            draw.rect()
+                .x_y(
+                    left + (col as f32 + 0.5) * cell,
+                    bottom + (row as f32 + 0.5) * cell,
+                )
+                .w_h(cell, cell)
+                .color(CONFIG.cell_color.to_srgb());
+        }
+    }
+}
+
+/// Opens the browser. Escape closes it instead of the game until it's closed.
+pub fn open_browser(app: &App, model: &mut Model) {
+    app.set_exit_on_escape(false);
+
+    let mut browser = SavestateBrowser::new();
+    browser.skip_next_char();
+    model.browser = Some(browser);
+}
+
+fn close_browser(app: &App, model: &mut Model) {
+    app.set_exit_on_escape(true);
+    model.browser = None;
+}
+
+/// Handles a key press while the browser is open.
+pub fn browser_key_pressed(app: &App, model: &mut Model, key: Key) {
+    let Some(mut browser) = model.browser.take() else {
+        return;
+    };
+    let confirm_delete = std::mem::take(&mut browser.confirm_delete);
+    // Any character after this is for a new key.
+    browser.skip_next_char = false;
+
+    match key {
+        Key::Escape if browser.rename.is_some() => browser.rename = None,
+        Key::Escape => return close_browser(app, model),
+        Key::Up => browser.selected = browser.selected.saturating_sub(1),
+        Key::Down => {
+            let last = browser.filtered().len().saturating_sub(1);
+            browser.selected = (browser.selected + 1).min(last);
+        }
+        Key::Back => match &mut browser.rename {
+            Some(name) => {
+                name.pop();
+            }
+            None => {
+                browser.search.pop();
+                browser.selected = 0;
+            }
+        },
+        Key::F2 => browser.rename = browser.selected_name(),
+        Key::Delete => {
+            if let Some(name) = browser.selected_name() {
+                if confirm_delete {
+                    match delete_savestate(&name) {
+                        Ok(()) => {
+                            notify_info(format!("Deleted '{}'.", name));
+                            if model.savestate_name.as_ref() == Some(&name) {
+                                model.savestate_name = None;
+                            }
+                        }
+                        Err(e) => notify_error(e),
+                    }
+                    browser.refresh();
+                } else {
+                    browser.confirm_delete = true;
+                }
+            }
+        }
+        Key::Return => {
+            let Some(name) = browser.selected_name() else {
+                model.browser = Some(browser);
+                return;
+            };
+
+            if let Some(new_name) = browser.rename.take() {
+                let new_name = new_name.trim().to_string();
+                match rename_savestate(&name, &new_name) {
+                    Ok(()) => {
+                        notify_info(format!("Renamed '{}' to '{}'.", name, new_name));
+                        if model.savestate_name.as_ref() == Some(&name) {
+                            model.savestate_name = Some(new_name);
+                        }
+                    }
+                    Err(e) => notify_error(e),
+                }
+                browser.refresh();
+            } else {
+                match open_savestate(model, name) {
+                    Ok(()) => {
+                        model.paused = true;
+                        notify_info("Savestate loaded.");
+                        return close_browser(app, model);
+                    }
+                    Err(e) => notify_error(e),
+                }
+            }
+        }
+        _ => (),
+    }
+
+    model.browser = Some(browser);
+}
@@ -0,0 +1,66 @@
+//! The prompt for naming the clip in the clipboard, which saves it so it can be used again in
+//! later sessions.
+
+use crate::prelude::*;
+
+/// Opens the prompt if there's something in the clipboard. Escape cancels it instead of closing
+/// the game.
+pub fn open_clip_name(app: &App, model: &mut Model) {
+    if model.clipboard.is_none() {
+        notify_error("The clipboard is empty.");
+        return;
+    }
+
+    app.set_exit_on_escape(false);
+    model.clip_name = Some(String::new());
+}
+
+fn close_clip_name(app: &App, model: &mut Model) {
+    app.set_exit_on_escape(true);
+    model.clip_name = None;
+}
+
+/// Handles a key press while the prompt is open. Typing is handled by `received_character`.
+pub fn clip_name_key_pressed(app: &App, model: &mut Model, key: Key) {
+    let Some(name) = &mut model.clip_name else {
+        return;
+    };
+
+    match key {
+        Key::Escape => close_clip_name(app, model),
+        Key::Back => {
+            name.pop();
+        }
+        Key::Return => {
+            let name = name.trim().to_string();
+            let Some(clipboard) = &model.clipboard else {
+                return close_clip_name(app, model);
+            };
+
+            match save_clip(&clip_dir(), &name, clipboard) {
+                Ok(()) => {
+                    notify_info(format!(
+                        "Clip saved as '{}', find it in the pattern library.",
+                        name
+                    ));
+                    close_clip_name(app, model);
+                }
+                Err(e) => notify_error(e),
+            }
+        }
+        _ => (),
+    }
+}
+
+pub fn draw_clip_name(draw: &Draw, cache: &Cache, model: &Model) {
+    if let Some(name) = &model.clip_name {
+        UiWindow::new()
+            .text(format!(
+                "Name the clip: {}_\n\nEnter: Save. Esc: Cancel.",
+                name
+            ))
+            .open(true)
+            .build()
+            .render(draw, cache, model);
+    }
+}
@@ -12,19 +12,38 @@ pub fn draw_info(draw: &Draw, model: &Model) {
         text = format!("{} (paused)", text);
     }
 
+    if model.paste.is_some() {
+        text = format!("{} (pasting, {})", text, model.paste_mode);
+    }
+
+    if let Some(recorder) = &model.recorder {
+        text = format!("{} (recording, {} frames)", text, recorder.frame_count());
+    }
+
     if model.show_info {
         text = format!(
-        "{}\ngrid: ({} x {})\nwindow: ({} x {})\nrulestring: {}\ncamera offset: ({:.1} x {:.1})\nzoom: {:.2}",
+        "{}\ngeneration: {}\ngrid: ({} x {})\nwindow: ({} x {})\nrulestring: {}\ncamera offset: ({:.1} x {:.1})\nzoom: {:.2}",
         text,
+        model.generation,
         model.cache.board_width,
         model.cache.board_height,
         model.cache.window_size.0,
         model.cache.window_size.1,
-        model.rulestring,
+        current_rule().serialize(),
         model.cache.camera_offset.0,
         model.cache.camera_offset.1,
         model.cache.scale_factor
     );
+
+        if !model.clipboard_history.is_empty() {
+            let clips: Vec<String> = model
+                .clipboard_history
+                .iter()
+                .enumerate()
+                .map(|(i, clip)| format!("{}: {}x{}", i + 1, clip.cols(), clip.rows()))
+                .collect();
+            text = format!("{}\nclips: {}", text, clips.join(", "));
+        }
     }
 
     if model.symmetry {
@@ -35,6 +54,10 @@ pub fn draw_info(draw: &Draw, model: &Model) {
         text = format!("{}\nGrid on", text);
     }
 
+    if model.selection_tool != SelectionTool::Rectangle {
+        text = format!("{}\nSelecting with the {}", text, model.selection_tool);
+    }
+
     #[cfg(debug_assertions)]
     {
         if model.show_info {
@@ -0,0 +1,150 @@
+//! The pattern picker, a window listing the patterns in the library.
+//!
+//! Typing filters the list by name or category. The arrow keys pick a pattern, and Enter copies it
+//! to the clipboard, ready to paste. Escape closes the picker instead of the game while it's open.
+
+use crate::prelude::*;
+
+/// How many patterns fit in the window at once.
+const VISIBLE_ROWS: usize = 8;
+/// Thumbnails are at most this many cells across.
+const THUMBNAIL_SIZE: usize = 48;
+
+#[derive(Clone, Debug, Default)]
+pub struct PatternPicker {
+    patterns: Vec<LibraryPattern>,
+    search: String,
+    /// Index into the filtered list.
+    selected: usize,
+}
+
+impl PatternPicker {
+    pub fn new() -> Self {
+        Self {
+            patterns: load_library(THUMBNAIL_SIZE),
+            ..Default::default()
+        }
+    }
+    /// The patterns whose names or categories match the search.
+    fn filtered(&self) -> Vec<&LibraryPattern> {
+        let search = self.search.to_lowercase();
+
+        self.patterns
+            .iter()
+            .filter(|pattern| {
+                pattern.name.to_lowercase().contains(&search)
+                    || pattern.category.to_lowercase().contains(&search)
+            })
+            .collect()
+    }
+    /// Adds a typed character to the search.
+    pub fn type_char(&mut self, c: char) {
+        if !c.is_control() {
+            self.search.push(c);
+            self.selected = 0;
+        }
+    }
+    pub fn render(&self, draw: &Draw, cache: &Cache, model: &Model) {
+        let filtered = self.filtered();
+        let first = self
+            .selected
+            .saturating_sub(VISIBLE_ROWS - 1)
+            .min(filtered.len().saturating_sub(VISIBLE_ROWS));
+        let rows = &filtered[first..filtered.len().min(first + VISIBLE_ROWS)];
+
+        let mut text = format!(
+            "Pattern library ({})    Search: {}\n",
+            filtered.len(),
+            self.search
+        );
+        for (i, pattern) in rows.iter().enumerate() {
+            text += &format!(
+                "{} {} ({}x{})\n    {}: {}\n",
+                if first + i == self.selected { ">" } else { " " },
+                pattern.name,
+                pattern.file.tiles.cols(),
+                pattern.file.tiles.rows(),
+                pattern.category,
+                pattern.description()
+            );
+        }
+        if filtered.is_empty() {
+            text += "No patterns found.\n";
+        }
+
+        // The header, two lines per row, a gap and the footer.
+        let lines = VISIBLE_ROWS * 2 + 3;
+        text += &"\n".repeat(lines - text.lines().count() - 1);
+        text += &format!(
+            "Enter: Copy to the clipboard. Esc: Close. Add your own to {}.",
+            library_dir()
+        );
+
+        let width = (cache.window_size.0 / 2.).min(800.);
+        let line_height = 1.25;
+        let line = CONFIG.font_size as f32 * line_height;
+
+        UiWindow::new()
+            .text(text)
+            .width(width)
+            .lines(lines)
+            .line_height(line_height)
+            .open(true)
+            .build()
+            .render(draw, cache, model);
+
+        // The thumbnails go on the right of each row, as tall as its two lines.
+        let size = line * 2. * 0.9;
+        let top = lines as f32 * line / 2.;
+        for (i, pattern) in rows.iter().enumerate() {
+            let x = width / 2. - size / 2.;
+            let y = top - (i as f32 * 2. + 2.) * line;
+            draw_thumbnail(draw, &pattern.thumbnail, x, y, size);
+        }
+    }
+}
+
+/// Opens the picker. Escape closes it instead of the game until it's closed.
+pub fn open_library(app: &App, model: &mut Model) {
+    app.set_exit_on_escape(false);
+    model.library = Some(PatternPicker::new());
+}
+
+fn close_library(app: &App, model: &mut Model) {
+    app.set_exit_on_escape(true);
+    model.library = None;
+}
+
+/// Handles a key press while the picker is open.
+pub fn library_key_pressed(app: &App, model: &mut Model, key: Key) {
+    let Some(mut picker) = model.library.take() else {
+        return;
+    };
+
+    match key {
+        Key::Escape => return close_library(app, model),
+        Key::Up => picker.selected = picker.selected.saturating_sub(1),
+        Key::Down => {
+            let last = picker.filtered().len().saturating_sub(1);
+            picker.selected = (picker.selected + 1).min(last);
+        }
+        Key::Back => {
+            picker.search.pop();
+            picker.selected = 0;
+        }
+        Key::Return => {
+            if let Some(pattern) = picker.filtered().get(picker.selected) {
+                apply_file_rule(&pattern.file);
+                set_clipboard(model, pattern.file.tiles.clone());
+                notify_info(format!(
+                    "{} copied to the clipboard, press Ctrl+V to paste it.",
+                    pattern.name
+                ));
+                return close_library(app, model);
+            }
+        }
+        _ => (),
+    }
+
+    model.library = Some(picker);
+}
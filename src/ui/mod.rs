@@ -1,9 +1,17 @@
 //! All the UI for the game.
 
+pub use self::browser::*;
+pub use self::clip_name::*;
 pub use self::info::*;
+pub use self::library::*;
 pub use self::notifications::*;
+pub use self::save_as::*;
 pub use self::windows::*;
 
+mod browser;
+mod clip_name;
 mod info;
+mod library;
 mod notifications;
+mod save_as;
 mod windows;
@@ -0,0 +1,53 @@
+//! The Save As prompt, for saving the board under a name of your choice.
+//!
+//! It won't overwrite an existing savestate, Ctrl+S is for that.
+
+use crate::prelude::*;
+
+/// Opens the prompt, starting with the name of the current savestate. Escape cancels it instead
+/// of closing the game.
+pub fn open_save_as(app: &App, model: &mut Model) {
+    app.set_exit_on_escape(false);
+    model.save_as = Some(model.savestate_name.clone().unwrap_or_default());
+}
+
+fn close_save_as(app: &App, model: &mut Model) {
+    app.set_exit_on_escape(true);
+    model.save_as = None;
+}
+
+/// Handles a key press while the prompt is open. Typing is handled by `received_character`.
+pub fn save_as_key_pressed(app: &App, model: &mut Model, key: Key) {
+    let Some(name) = &mut model.save_as else {
+        return;
+    };
+
+    match key {
+        Key::Escape => close_save_as(app, model),
+        Key::Back => {
+            name.pop();
+        }
+        Key::Return => {
+            let name = name.trim().to_string();
+            match write_savestate(model, &name, false) {
+                Ok(()) => {
+                    notify_info(format!("Board saved as '{}'.", name));
+                    model.savestate_name = Some(name);
+                    close_save_as(app, model);
+                }
+                Err(e) => notify_error(e),
+            }
+        }
+        _ => (),
+    }
+}
+
+pub fn draw_save_as(draw: &Draw, cache: &Cache, model: &Model) {
+    if let Some(name) = &model.save_as {
+        UiWindow::new()
+            .text(format!("Save as: {}_\n\nEnter: Save. Esc: Cancel.", name))
+            .open(true)
+            .build()
+            .render(draw, cache, model);
+    }
+}
@@ -1,6 +1,6 @@
 //! Logic for rendering a window to the center of the screen.
 //!
-//! Used for the keybinds menu.
+//! Used for the keybinds menu, the analysis report and the savestate browser.
 
 use crate::config::Color;
 use crate::prelude::*;